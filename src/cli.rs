@@ -1,10 +1,165 @@
-use clap::Parser;
+use std::{io::IsTerminal, ops::RangeInclusive};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crate::lang::{edition::Edition, error::{MessageFormat, ReportConfig}, lint::{Level, Lint, LintLevels}};
 
 #[derive(Parser)]
 #[command(author, version, about)]
 pub struct Cli {
-    #[arg(index=1, help="The path of the program source code")]
+    #[command(subcommand)]
+    pub command: Command,
+    #[arg(long, global=true, value_enum, default_value_t=MessageFormat::Human, help="The format that errors & warnings are printed in")]
+    pub message_format: MessageFormat,
+    #[arg(long, global=true, value_enum, default_value_t=ColorChoice::Auto, help="When to colour errors & warnings")]
+    pub color: ColorChoice,
+}
+
+impl Cli {
+    /// How diagnostics should be printed
+    pub fn report_config(&self) -> ReportConfig {
+        ReportConfig { format: self.message_format, color: self.color.enabled() }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Compiles a program into a scratch binary
+    Build {
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(short, long, help="The desired output path of the compiled scratch binary (defaults to the input path with an `.sb3` extension, unless `--emit` is used)")]
+        output_file: Option<String>,
+        #[arg(long, value_enum, value_delimiter=',', help="Intermediate compiler stages to print to stdout (eg. `--emit tokens,typed`), the scratch binary is then only written if `-o` is given")]
+        emit: Vec<EmitStage>,
+        #[arg(long, value_delimiter=',', value_parser=parse_char_range, default_value="9-10,32-126", help="The unicode code-point ranges supported by `char_code!` and `from_char_code!` (eg. `32-126,160-255`)")]
+        charset: Vec<RangeInclusive<u32>>,
+    },
+    /// Parses & type-checks a program without compiling it
+    Check {
+        #[command(flatten)]
+        source: SourceArgs,
+    },
+    /// Runs a program locally (printing to stdout & reading input from stdin)
+    Run {
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(long, value_delimiter=',', value_parser=parse_char_range, default_value="9-10,32-126", help="The unicode code-point ranges supported by `char_code!` and `from_char_code!` (eg. `32-126,160-255`)")]
+        charset: Vec<RangeInclusive<u32>>,
+        #[arg(long, help="Run the compiled scratch assembly in an emulator of the scratch vm instead (to check what the compiler generates)")]
+        emulate: bool,
+    },
+    /// Runs a program locally in a step debugger (pausing before statements to inspect variables)
+    Debug {
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(long, value_delimiter=',', value_parser=parse_char_range, default_value="9-10,32-126", help="The unicode code-point ranges supported by `char_code!` and `from_char_code!` (eg. `32-126,160-255`)")]
+        charset: Vec<RangeInclusive<u32>>,
+        #[arg(short, long="break", value_name="LINE", value_delimiter=',', help="Lines to pause before (eg. `-b 4,12`), otherwise the debugger pauses before the first statement")]
+        breakpoints: Vec<usize>,
+    },
+    /// Runs the tests (`test fn`s) of a program locally
+    Test {
+        #[command(flatten)]
+        source: SourceArgs,
+        #[arg(long, value_delimiter=',', value_parser=parse_char_range, default_value="9-10,32-126", help="The unicode code-point ranges supported by `char_code!` and `from_char_code!` (eg. `32-126,160-255`)")]
+        charset: Vec<RangeInclusive<u32>>,
+    },
+    /// Starts an interactive session for evaluating statements & defining functions one at a time
+    Repl {
+        #[arg(long, help="The language edition to evaluate with (defaults to `2023`)")]
+        edition: Option<Edition>,
+        #[arg(long, value_delimiter=',', value_parser=parse_char_range, default_value="9-10,32-126", help="The unicode code-point ranges supported by `char_code!` and `from_char_code!` (eg. `32-126,160-255`)")]
+        charset: Vec<RangeInclusive<u32>>,
+    },
+    /// Formats source files in the canonical style
+    Fmt {
+        #[arg(required=true, help="The paths of the source files to format")]
+        files: Vec<String>,
+        #[arg(long, help="Only check if the files are formatted, exiting with a non-zero status if any of them would change")]
+        check: bool,
+        #[arg(long, help="The language edition to format with (overrides the edition in the project manifest, defaults to `2023`)")]
+        edition: Option<Edition>,
+    },
+    /// Starts a language server over stdio
+    Lsp {
+        #[arg(long, help="The language edition to analyse with (overrides the edition in the project manifests, defaults to `2023`)")]
+        edition: Option<Edition>,
+    },
+}
+
+/// The arguments shared by commands that compile a single program
+#[derive(Args)]
+pub struct SourceArgs {
+    #[arg(help="The path of the program source code")]
     pub input_file: String,
-    #[arg(index=2, help="The desired output path of the compiled scratch binary")]
-    pub output_file: String,
+    #[arg(long, help="The language edition to compile with (overrides the edition in the project manifest, defaults to `2023`)")]
+    pub edition: Option<Edition>,
+    #[arg(short='A', long="allow", value_name="LINT", value_delimiter=',', help="Lints to allow (overridden by lint directives like `#![warn(...)]` in the source)")]
+    pub allow: Vec<Lint>,
+    #[arg(short='W', long="warn", value_name="LINT", value_delimiter=',', help="Lints to warn about (overridden by lint directives like `#![allow(...)]` in the source)")]
+    pub warn: Vec<Lint>,
+    #[arg(short='D', long="deny", value_name="LINT", value_delimiter=',', help="Lints to report as errors (overridden by lint directives like `#![allow(...)]` in the source)")]
+    pub deny: Vec<Lint>,
+}
+
+impl SourceArgs {
+    /// The lint levels given on the cli (denying takes priority over warning, which takes priority over allowing)
+    pub fn lint_levels(&self) -> LintLevels {
+        let mut levels = LintLevels::default();
+        for (lints, level) in [(&self.allow, Level::Allow), (&self.warn, Level::Warn), (&self.deny, Level::Deny)] {
+            for lint in lints {
+                levels.set(*lint, level);
+            }
+        }
+        levels
+    }
+}
+
+/// An intermediate stage of the compiler that can be printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmitStage {
+    /// The token stream from the lexer
+    Tokens,
+    /// The roots from the parser
+    Ast,
+    /// The type-checked project
+    Typed,
+    /// The scratch assembly
+    Assembly,
+    /// The raw (pretty-printed) `project.json` of the scratch binary
+    ProjectJson,
+}
+
+/// When to colour diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only when printing to a terminal (and `NO_COLOR` isn't set)
+    Auto,
+    /// Always colour diagnostics
+    Always,
+    /// Never colour diagnostics
+    Never,
+}
+
+impl ColorChoice {
+    /// If diagnostics should be coloured
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Parses a code-point range like `32-126` (or a single code-point like `10`)
+fn parse_char_range(range: &str) -> Result<RangeInclusive<u32>, String> {
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let start = start.trim().parse::<u32>().map_err(|err| format!("invalid code-point `{start}`: {err}"))?;
+    let end = end.trim().parse::<u32>().map_err(|err| format!("invalid code-point `{end}`: {err}"))?;
+
+    if start > end {
+        return Err(format!("the start of the range `{range}` is larger than it's end"));
+    }
+
+    Ok(start..=end)
 }
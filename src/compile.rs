@@ -0,0 +1,162 @@
+//! The compiler as a library, compiling source code in-memory (without touching the filesystem or exiting the process)
+
+use std::{fmt::Display, io::{Cursor, Seek, Write}, ops::RangeInclusive};
+use json::JsonValue;
+use logos::Logos;
+use crate::{lang::{edition::{self, Edition}, error::{warning::Warning, Diagnostic, ReportConfig, Reportable, Severity}, lint::{self, LintLevels}, parser::{self, root::Roots}, targets, token::Token, typed::{self, root::Project}}, scratch::{self, Assembly}};
+
+/// The unicode code-point ranges supported by `char_code!` and `from_char_code!` by default (tabs, newlines & printable ascii)
+pub const DEFAULT_CHARSET: &[RangeInclusive<u32>] = &[9..=10, 32..=126];
+
+/// Options for compiling a program
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// The language edition to compile with
+    pub edition: Edition,
+    /// The unicode code-point ranges supported by `char_code!` and `from_char_code!`
+    pub charset: Vec<RangeInclusive<u32>>,
+    /// The levels of lints (lint directives in the source take priority over these)
+    pub lints: LintLevels,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            edition: Edition::default(),
+            charset: DEFAULT_CHARSET.to_vec(),
+            lints: LintLevels::default(),
+        }
+    }
+}
+
+/// An intermediate stage of compilation (for inspecting what the compiler generates)
+#[derive(Debug)]
+pub enum Stage<'a> {
+    /// The parsed roots of the source file
+    Ast(&'a Roots),
+    /// The type-checked project
+    Typed(&'a Project),
+    /// The scratch assembly translated from the project
+    Assembly(&'a Assembly),
+    /// The assembled scratch `project.json`
+    ProjectJson(&'a JsonValue),
+}
+
+/// A successfully compiled program
+#[derive(Debug, Clone)]
+pub struct CompiledProject {
+    /// The scratch `project.json`
+    pub json: JsonValue,
+    /// Any warnings produced while compiling
+    pub warnings: Diagnostics,
+}
+
+impl CompiledProject {
+    /// Writes the project as a `.sb3` to any writer
+    pub fn write_sb3(&self, writer: impl Write + Seek) -> std::io::Result<()> {
+        scratch::write_sb3(writer, &self.json)
+    }
+
+    /// The bytes of the project as a `.sb3`
+    pub fn to_sb3(&self) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        self.write_sb3(&mut bytes).expect("writing to memory shouldn't fail");
+        bytes.into_inner()
+    }
+}
+
+/// The errors & warnings of a source file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Diagnostics {
+    /// If any of the diagnostics are errors
+    pub fn has_errors(&self) -> bool {
+        self.0.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// Prints all of the diagnostics to stderr
+    pub fn report(&self, src_id: &str, src: &str, config: &ReportConfig) {
+        for diagnostic in self.0.iter() {
+            diagnostic.report(src_id, src, config);
+        }
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, diagnostic) in self.0.iter().enumerate() {
+            if i != 0 { writeln!(f)? }
+            match diagnostic.code {
+                Some(code) => write!(f, "{}[{code}]: {}", diagnostic.severity, diagnostic.message)?,
+                None => write!(f, "{}: {}", diagnostic.severity, diagnostic.message)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// The warnings about a source file that don't depend on it compiling (like edition migrations)
+pub fn warnings(src: &str, edition: Edition) -> Diagnostics {
+    let mut warnings = Diagnostics::default();
+
+    // warn about identifiers that would change meaning in the latest edition
+    if edition != Edition::LATEST {
+        let idents = edition::migration_idents(src);
+        if !idents.is_empty() {
+            warnings.0.push(Warning::EditionMigration { edition, idents }.diagnostic());
+        }
+    }
+
+    warnings
+}
+
+/// Lexes & parses a source file into it's roots
+pub fn parse(src: &str, edition: Edition) -> Result<Roots, Diagnostics> {
+    parser::root::parse_root(&mut Token::lexer_with_extras(src, edition.into()).spanned())
+        .map_err(|errors| Diagnostics(errors.iter().map(Reportable::diagnostic).collect()))
+}
+
+/// Type-checks the roots of a source file
+pub fn type_check(roots: &Roots) -> Result<Project, Diagnostics> {
+    typed::root::wrap_root(roots)
+        .map_err(|error| Diagnostics(vec![error.diagnostic()]))
+}
+
+/// Lints a type-checked source file (denied lints are errors)
+pub fn lint(src: &str, edition: Edition, roots: &Roots, project: &Project, levels: &LintLevels) -> Diagnostics {
+    Diagnostics(lint::lint(src, edition, roots, project, levels))
+}
+
+/// Compiles a source file (named `src_id`, which `dbg` prints) into a scratch project
+pub fn compile(src_id: &str, src: &str, options: &CompileOptions) -> Result<CompiledProject, Diagnostics> {
+    compile_with_stages(src_id, src, options, |_| ())
+}
+
+/// Compiles a source file like [`compile`], passing each intermediate stage to `on_stage` as it's reached (so before any errors of later stages)
+pub fn compile_with_stages(src_id: &str, src: &str, options: &CompileOptions, mut on_stage: impl FnMut(Stage)) -> Result<CompiledProject, Diagnostics> {
+    let mut warnings = warnings(src, options.edition);
+    let with_warnings = |mut errors: Diagnostics| {
+        errors.0.splice(0..0, warnings.0.clone());
+        errors
+    };
+
+    let roots = parse(src, options.edition).map_err(with_warnings)?;
+    on_stage(Stage::Ast(&roots));
+    let project = type_check(&roots).map_err(with_warnings)?;
+    on_stage(Stage::Typed(&project));
+
+    // denied lints stop compilation
+    warnings.0.extend(lint(src, options.edition, &roots, &project, &options.lints).0);
+    if warnings.has_errors() {
+        return Err(warnings);
+    }
+    let assembly = targets::scratch::translate(project, src_id, src, &options.charset);
+    on_stage(Stage::Assembly(&assembly));
+    let json = scratch::add_console("console", scratch::assemble(assembly));
+    on_stage(Stage::ProjectJson(&json));
+
+    Ok(CompiledProject { json, warnings })
+}
@@ -1,7 +1,15 @@
 pub mod error;
+pub mod edition;
 pub mod token;
+pub mod cst;
+pub mod fmt;
+pub mod analysis;
+pub mod lint;
 pub mod parser;
 pub mod typed;
 pub mod targets;
+pub mod interpreter;
+pub mod debugger;
+pub mod repl;
 
 pub type Spanned<T> = (T, ketchup::Span);
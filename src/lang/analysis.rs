@@ -0,0 +1,348 @@
+//! Static analysis of a source file for tooling (like the language server), gathering diagnostics, types & references
+//!
+//! the types of expressions & references to variables are only known once the whole file type-checks, until then only the
+//! functions (along with calls to them) and the parameters of functions (along with their usages) are analysed
+
+use ketchup::Span;
+use logos::Logos;
+use crate::lang::{edition::{self, Edition}, error::{warning::Warning, Diagnostic, Reportable}, lint::{self, LintLevels}, parser::{self, block::Block, expr::{Expr, ExprOper}, stmt::Stmt}, token::Token, typed::{self, block::TBlock, builtin::{FormatSegment, TBuiltinFnCall}, expr::TExpr, stmt::TStmt, types::Type}, Spanned};
+
+/// A user-defined function
+#[derive(Debug, Clone)]
+pub struct FuncInfo {
+    pub ident: String,
+    /// The signature of the function (like `fn add(a: num, b: num) -> num`)
+    pub signature: String,
+    /// The span of the function's identifier
+    pub ident_span: Span,
+    /// The doc comments of the function
+    pub docs: Vec<String>,
+}
+
+/// The results of analysing a source file
+#[derive(Debug, Default)]
+pub struct Analysis {
+    /// All the errors & warnings in the source file
+    pub diagnostics: Vec<Diagnostic>,
+    /// The types of expressions & variable definitions
+    pub types: Vec<Spanned<Type>>,
+    /// Usages of variables & functions along with the span of their definitions
+    pub references: Vec<(Span, Span)>,
+    /// All of the user-defined functions
+    pub funcs: Vec<FuncInfo>,
+}
+
+impl Analysis {
+    /// Finds the type of the smallest expression at a byte offset
+    pub fn type_at(&self, offset: usize) -> Option<&Spanned<Type>> {
+        self.types.iter()
+            .filter(|(_, span)| span.contains(&offset))
+            .min_by_key(|(_, span)| span.len())
+    }
+
+    /// Finds the definition of the variable or function used at a byte offset
+    pub fn definition_at(&self, offset: usize) -> Option<&Span> {
+        self.references.iter()
+            .filter(|(span, _)| span.contains(&offset))
+            .min_by_key(|(span, _)| span.len())
+            .map(|(_, def)| def)
+    }
+
+    /// Finds the function whose identifier (either in it's definition or a call) is at a byte offset
+    pub fn func_at(&self, offset: usize) -> Option<&FuncInfo> {
+        let def = self.definition_at(offset).cloned();
+        self.funcs.iter().find(|func| func.ident_span.contains(&offset) || Some(&func.ident_span) == def.as_ref())
+    }
+}
+
+/// Analyses a source file (never fails, as any errors are part of the analysis)
+pub fn analyse(src: &str, edition: Edition) -> Analysis {
+    let mut analysis = Analysis::default();
+
+    // warn about identifiers that would change meaning in the latest edition
+    if edition != Edition::LATEST {
+        let idents = edition::migration_idents(src);
+        if !idents.is_empty() {
+            analysis.diagnostics.push(Warning::EditionMigration { edition, idents }.diagnostic());
+        }
+    }
+
+    // parse the source
+    let roots = match parser::root::parse_root(&mut Token::lexer_with_extras(src, edition.into()).spanned()) {
+        Ok(roots) => roots,
+        Err(errors) => {
+            analysis.diagnostics.extend(errors.iter().map(Reportable::diagnostic));
+            return analysis;
+        },
+    };
+
+    // gather the function definitions
+    let mut walker = Walker { src, analysis, defs: Vec::new() };
+    let mut func_params = Vec::new();
+    for (func, span) in roots.funcs.iter() {
+        let ident_span = find_ident(src, span, &func.ident);
+        let params = func.params.iter()
+            .map(|((ident, ptype), _)| format!("{ident}: {ptype}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        walker.analysis.funcs.push(FuncInfo {
+            ident: func.ident.clone(),
+            signature: format!("{}fn {}({params}) -> {}", if func.test { "test " } else { "" }, func.ident, func.retrn_type.0),
+            ident_span,
+            docs: func.docs.clone(),
+        });
+
+        // parameters are variable definitions too
+        let params = func.params.iter()
+            .map(|((ident, ptype), span)| (ident.as_str(), find_ident(src, span, ident), ptype.clone()))
+            .collect::<Vec<_>>();
+        for (ident, span, ptype) in params.iter() {
+            walker.defs.push((format!("$func${}/{ident}", func.ident), span.clone()));
+            walker.analysis.types.push((ptype.clone(), span.clone()));
+        }
+        func_params.push(params);
+    }
+
+    // type-check the project
+    let project = match typed::root::wrap_root(&roots) {
+        Ok(project) => project,
+        Err(err) => {
+            walker.analysis.diagnostics.push(err.diagnostic());
+
+            // still gather what's known without type-checking, so tooling keeps working while the file is being edited
+            for ((func, _), params) in roots.funcs.iter().zip(func_params) {
+                walker.parsed_block(&func.body.0, params);
+            }
+            for (main, _) in roots.main.iter() {
+                walker.parsed_block(main, Vec::new());
+            }
+            return walker.analysis;
+        },
+    };
+
+    walker.block(&project.main);
+    for (_, block) in project.procedures.iter().chain(project.tests.iter()) {
+        walker.block(block);
+    }
+    walker.analysis.diagnostics.extend(lint::lint(src, edition, &roots, &project, &LintLevels::default()));
+
+    walker.analysis
+}
+
+/// Finds the span of an identifier within a section of the source (or the section itself if it can't be found)
+pub(crate) fn find_ident(src: &str, span: &Span, ident: &str) -> Span {
+    let section = &src[span.clone()];
+    let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+    section.match_indices(ident)
+        .find(|(i, _)| {
+            !section[..*i].ends_with(is_ident_char) && !section[i + ident.len()..].starts_with(is_ident_char)
+        })
+        .map(|(i, _)| span.start + i..span.start + i + ident.len())
+        .unwrap_or_else(|| span.clone())
+}
+
+/// Walks the typed tree, gathering types & references
+struct Walker<'a> {
+    src: &'a str,
+    analysis: Analysis,
+    /// The definitions of variables (by their unique identifiers) in order of appearance
+    defs: Vec<(String, Span)>,
+}
+
+impl Walker<'_> {
+    fn block(&mut self, block: &TBlock) {
+        for ((stmt, span), stmt_type) in block.stmts.iter().chain(block.tail.iter()) {
+            self.stmt(stmt, Some(span), stmt_type);
+        }
+    }
+
+    /// Walks a statement (the span is only known for statements directly within blocks)
+    fn stmt(&mut self, stmt: &TStmt, span: Option<&Span>, stmt_type: &Type) {
+        match stmt {
+            TStmt::Expr(expr) => self.expr(expr, span, Some(stmt_type)),
+
+            TStmt::VarDeclare { ident, value: ((value, value_span), value_type), .. } => {
+                // the identifier is the last part of the unique identifier
+                let name = ident.rsplit('/').next().unwrap_or(ident);
+                let start = span.map(|span| span.start).unwrap_or(value_span.start);
+                let ident_span = find_ident(self.src, &(start..value_span.start), name);
+
+                self.expr(value, Some(value_span), Some(value_type));
+                self.defs.push((ident.clone(), ident_span.clone()));
+                self.analysis.types.push((value_type.clone(), ident_span));
+            },
+
+            TStmt::VarMutate { ident, value: (value, value_type) } => match value {
+                // skip the variable get that's generated by operations like `+=`
+                TExpr::Add(lhs, rhs) | TExpr::Sub(lhs, rhs) | TExpr::Mul(lhs, rhs) | TExpr::Div(lhs, rhs) | TExpr::Mod(lhs, rhs)
+                    if matches!(&lhs.0.0, TExpr::VarGet { ident: var, .. } if var == ident) && lhs.0.1 == rhs.0.1 => self.typed_expr(rhs),
+                _ => self.expr(value, None, Some(value_type)),
+            },
+
+            TStmt::If { cond, body, otherwise } => {
+                self.typed_expr(cond);
+                self.stmt(&body.0, None, &body.1);
+                if let Some(otherwise) = otherwise {
+                    self.stmt(&otherwise.0, None, &otherwise.1);
+                }
+            },
+
+            TStmt::While { cond, body } => {
+                self.typed_expr(cond);
+                self.stmt(&body.0, None, &body.1);
+            },
+        }
+    }
+
+    fn typed_expr(&mut self, ((expr, span), expr_type): &(Spanned<TExpr>, Type)) {
+        self.expr(expr, Some(span), Some(expr_type));
+    }
+
+    fn expr(&mut self, expr: &TExpr, span: Option<&Span>, expr_type: Option<&Type>) {
+        if let (Some(span), Some(expr_type)) = (span, expr_type) {
+            self.analysis.types.push((expr_type.clone(), span.clone()));
+        }
+
+        match expr {
+            TExpr::Number(_) | TExpr::String(_) | TExpr::Bool(_) | TExpr::Nil => (),
+
+            TExpr::Add(lhs, rhs) | TExpr::Sub(lhs, rhs) | TExpr::Mul(lhs, rhs) | TExpr::Div(lhs, rhs) | TExpr::Mod(lhs, rhs)
+            | TExpr::Concat(lhs, rhs) | TExpr::Or(lhs, rhs) | TExpr::And(lhs, rhs)
+            | TExpr::EE(lhs, rhs) | TExpr::NE(lhs, rhs) | TExpr::GT(lhs, rhs) | TExpr::LT(lhs, rhs) | TExpr::GTE(lhs, rhs) | TExpr::LTE(lhs, rhs) => {
+                self.typed_expr(lhs);
+                self.typed_expr(rhs);
+            },
+
+            TExpr::Neg(expr) | TExpr::Pos(expr) | TExpr::Not(expr) => self.typed_expr(expr),
+            TExpr::Tuple(exprs) => exprs.iter().for_each(|expr| self.typed_expr(expr)),
+            TExpr::Block(block) => self.block(block),
+
+            TExpr::List(list_type, exprs) => for (expr, span) in exprs {
+                self.expr(expr, Some(span), Some(list_type));
+            },
+
+            TExpr::Call(ident, args) => {
+                if let (Some(span), Some(func)) = (span, self.analysis.funcs.iter().find(|func| &func.ident == ident)) {
+                    self.analysis.references.push((span.start..span.start + ident.len(), func.ident_span.clone()));
+                }
+                for (_, arg) in args {
+                    self.typed_expr(arg);
+                }
+            },
+
+            TExpr::VarGet { ident, var_type } => if let Some(span) = span {
+                self.analysis.types.push((var_type.clone(), span.clone()));
+
+                // the definition is the latest one before the usage
+                if let Some((_, def)) = self.defs.iter().rev().find(|(def, def_span)| def == ident && def_span.start < span.start) {
+                    self.analysis.references.push((span.clone(), def.clone()));
+                }
+            },
+
+            TExpr::BuiltinFnCall(call) => self.builtin(call),
+        }
+    }
+
+    /// Walks a block of the parsed tree (for when the project doesn't type-check), gathering calls to functions & usages of the `params` (along with their types)
+    fn parsed_block(&mut self, block: &Block, mut params: Vec<(&str, Span, Type)>) {
+        for (stmt, _) in block.stmts.iter().chain(block.tail.iter()) {
+            self.parsed_stmt(stmt, &mut params);
+        }
+    }
+
+    fn parsed_stmt(&mut self, stmt: &Stmt, params: &mut Vec<(&str, Span, Type)>) {
+        match stmt {
+            Stmt::Expr(expr) => self.parsed_expr(expr, params),
+
+            // parameters that are shadowed are no longer parameters for the rest of the block
+            Stmt::VarDeclare { ident, value, .. } => {
+                self.parsed_expr(value, params);
+                params.retain(|(param, _, _)| param != ident);
+            },
+
+            Stmt::VarMutate { ident: (ident, span), value } | Stmt::VarMutateAdd { ident: (ident, span), value }
+            | Stmt::VarMutateSub { ident: (ident, span), value } | Stmt::VarMutateMul { ident: (ident, span), value }
+            | Stmt::VarMutateDiv { ident: (ident, span), value } | Stmt::VarMutateMod { ident: (ident, span), value } => {
+                self.parsed_param(ident, span, params);
+                self.parsed_expr(value, params);
+            },
+
+            Stmt::If { cond, body, otherwise } => {
+                self.parsed_expr(cond, params);
+                self.parsed_stmt(&body.0, &mut params.clone());
+                if let Some(otherwise) = otherwise {
+                    self.parsed_stmt(&otherwise.0, &mut params.clone());
+                }
+            },
+
+            Stmt::While { cond, body } => {
+                self.parsed_expr(cond, params);
+                self.parsed_stmt(&body.0, &mut params.clone());
+            },
+        }
+    }
+
+    fn parsed_expr(&mut self, expr: &Expr, params: &[(&str, Span, Type)]) {
+        for node in expr.asa.iter() {
+            match &node.oper {
+                ExprOper::Ident(ident) => self.parsed_param(ident, &node.info.span, params),
+
+                ExprOper::Call((ident, span), args) => {
+                    if let Some(func) = self.analysis.funcs.iter().find(|func| &func.ident == ident) {
+                        self.analysis.references.push((span.clone(), func.ident_span.clone()));
+                    }
+                    args.iter().for_each(|arg| self.parsed_expr(arg, params));
+                },
+                ExprOper::Tuple(exprs) | ExprOper::List(exprs) | ExprOper::BuiltinFnCall { args: exprs, .. } => {
+                    exprs.iter().for_each(|expr| self.parsed_expr(expr, params));
+                },
+                ExprOper::Block(block) => self.parsed_block(block, params.to_vec()),
+
+                _ => (),
+            }
+        }
+    }
+
+    /// Gathers the usage of a variable if it's one of the `params`
+    fn parsed_param(&mut self, ident: &str, span: &Span, params: &[(&str, Span, Type)]) {
+        if let Some((_, def, ptype)) = params.iter().find(|(param, _, _)| *param == ident) {
+            self.analysis.references.push((span.clone(), def.clone()));
+            self.analysis.types.push((ptype.clone(), span.clone()));
+        }
+    }
+
+    fn builtin(&mut self, call: &TBuiltinFnCall) {
+        use TBuiltinFnCall as B;
+
+        let exprs = match call {
+            B::Timer | B::PrintLn(None) | B::Panic(_, None) => vec![],
+            B::PrintLn(Some(expr)) | B::Input(expr) | B::Panic(_, Some(expr)) | B::ListLen(expr) | B::StringLen(expr) | B::FromCharCode(expr)
+            | B::ListClear(expr) | B::ListReverse(expr) | B::ListSort(expr) => vec![expr],
+            B::AsString(expr, expr_type) => return self.expr(&expr.0, Some(&expr.1), Some(expr_type)),
+            B::ListGet { list, idx, .. } | B::ListRemove { list, idx, .. } => vec![list, idx],
+            B::ListPush { list, expr } | B::ListContains { list, expr } | B::ListIndexOf { list, expr } => vec![list, expr],
+            B::ListInsert { list, idx, expr, .. } | B::ListReplace { list, idx, expr, .. } => vec![list, idx, expr],
+            B::StringGet { string, idx, .. } | B::CharCode { string, idx } => vec![string, idx],
+            B::Assert { cond, message, .. } => std::iter::once(cond).chain(message).collect(),
+            B::AssertEq { lhs, rhs, .. } => {
+                self.expr(&lhs.0.0, Some(&lhs.0.1), Some(&lhs.1));
+                return self.expr(&rhs.0.0, Some(&rhs.0.1), Some(&rhs.1));
+            },
+            B::Dbg { expr, .. } => return self.expr(&expr.0.0, Some(&expr.0.1), Some(&expr.1)),
+            B::Format(segments) => {
+                for segment in segments {
+                    if let FormatSegment::Arg((expr, span), expr_type) = segment {
+                        self.expr(expr, Some(span), Some(expr_type));
+                    }
+                }
+                return;
+            },
+        };
+
+        for (expr, span) in exprs {
+            self.expr(expr, Some(span), None);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! A lossless concrete syntax tree (keeps all whitespace & comments) that lives alongside the regular parser output
+//!
+//! (unlike `lang::parser` it never fails, so it's useful for tooling like formatters, refactoring tools & the lsp, even on broken code)
+
+use ketchup::Span;
+use logos::Logos;
+use crate::lang::{edition::Edition, error::parser::Error, token::Token};
+
+/// The kind of a trivia token (tokens that are skipped by the regular lexer)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs & newlines
+    Whitespace,
+    /// A `//` or `#` comment
+    LineComment,
+    /// A (possibly nested) `/* */` comment
+    BlockComment,
+}
+
+/// The kind of a token in the concrete syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstTokenKind {
+    /// A regular token from the lexer
+    Token(Token),
+    /// Whitespace or comments
+    Trivia(TriviaKind),
+    /// Source that the lexer failed to lex
+    Error(Error),
+}
+
+/// A token in the concrete syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstToken {
+    pub kind: CstTokenKind,
+    pub span: Span,
+}
+
+/// The kind of a node in the concrete syntax tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The entire source file
+    Root,
+    /// The main procedure (including it's doc comments)
+    Main,
+    /// A function definition (including it's doc comments)
+    FuncDef,
+    /// A `{ ... }` block
+    Block,
+    /// A statement within a block (excluding the `;`)
+    Stmt,
+    /// A `( ... )` group (parentheses, tuples, function args & params)
+    Parens,
+    /// A `[ ... ]` group (lists)
+    Brackets,
+}
+
+/// An element of a node (either a child node or a token)
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstElement {
+    Node(CstNode),
+    Token(CstToken),
+}
+
+/// A node in the concrete syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode {
+    pub kind: NodeKind,
+    /// The span of all of the node's children
+    pub span: Span,
+    pub children: Vec<CstElement>,
+}
+
+impl CstElement {
+    /// The span of the element
+    pub fn span(&self) -> Span {
+        match self {
+            CstElement::Node(node) => node.span.clone(),
+            CstElement::Token(token) => token.span.clone(),
+        }
+    }
+}
+
+impl CstNode {
+    /// Creates a node and computes it's span from it's children (empty nodes are placed at `pos`)
+    fn new(kind: NodeKind, children: Vec<CstElement>, pos: usize) -> Self {
+        let span = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => first.span().start..last.span().end,
+            _ => pos..pos,
+        };
+
+        Self { kind, span, children }
+    }
+
+    /// The source text of the node (including all of it's trivia)
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.span.clone()]
+    }
+
+    /// All of the tokens (including trivia) within the node, in order
+    pub fn tokens(&self) -> Vec<&CstToken> {
+        let mut tokens = Vec::new();
+        for child in self.children.iter() {
+            match child {
+                CstElement::Node(node) => tokens.append(&mut node.tokens()),
+                CstElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// The child nodes of the node
+    pub fn child_nodes(&self) -> impl Iterator<Item = &CstNode> {
+        self.children.iter().filter_map(|child| match child {
+            CstElement::Node(node) => Some(node),
+            CstElement::Token(_) => None,
+        })
+    }
+
+    /// Finds the token at a byte offset in the source
+    pub fn token_at(&self, offset: usize) -> Option<&CstToken> {
+        self.tokens().into_iter().find(|token| token.span.contains(&offset))
+    }
+
+    /// Finds the chain of nodes (from this node to the deepest one) that contain a byte offset in the source
+    pub fn nodes_at(&self, offset: usize) -> Vec<&CstNode> {
+        let mut nodes = vec![self];
+        while let Some(node) = nodes.last().unwrap().child_nodes().find(|node| node.span.contains(&offset)) {
+            nodes.push(node);
+        }
+        nodes
+    }
+}
+
+/// Lexes the source into tokens without skipping any whitespace or comments (concatinating the tokens yields the original source)
+pub fn lex(src: &str, edition: Edition) -> Vec<CstToken> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    for (token, span) in Token::lexer_with_extras(src, edition.into()).spanned() {
+        // anything skipped by the lexer is trivia
+        lex_trivia(src, pos..span.start, &mut tokens);
+        pos = span.end;
+
+        tokens.push(CstToken {
+            kind: match token {
+                Ok(token) => CstTokenKind::Token(token),
+                Err(err) => CstTokenKind::Error(err),
+            },
+            span,
+        });
+    }
+
+    lex_trivia(src, pos..src.len(), &mut tokens);
+    tokens
+}
+
+/// Splits a section of the source that was skipped by the lexer into trivia tokens
+fn lex_trivia(src: &str, span: Span, tokens: &mut Vec<CstToken>) {
+    let mut pos = span.start;
+
+    while pos < span.end {
+        let rest = &src[pos..span.end];
+
+        let (kind, len) = if rest.starts_with("/*") {
+            (CstTokenKind::Trivia(TriviaKind::BlockComment), block_comment_len(rest))
+        } else if rest.starts_with("//") || rest.starts_with('#') {
+            (CstTokenKind::Trivia(TriviaKind::LineComment), rest.find(['\n', '\r']).unwrap_or(rest.len()))
+        } else if rest.starts_with(char::is_whitespace) {
+            (CstTokenKind::Trivia(TriviaKind::Whitespace), rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len()))
+        } else {
+            // shouldn't be reachable, but never lose any of the source
+            (CstTokenKind::Error(Error::UnexpectedCharacter), rest.chars().next().map(char::len_utf8).unwrap_or(1))
+        };
+
+        tokens.push(CstToken { kind, span: pos..pos + len });
+        pos += len;
+    }
+}
+
+/// The length of a (possibly nested) block comment at the start of `src`
+fn block_comment_len(src: &str) -> usize {
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < src.len() {
+        if src[i..].starts_with("/*") {
+            depth += 1;
+            i += 2;
+        } else if src[i..].starts_with("*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 { return i }
+        } else {
+            i += src[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+    }
+
+    src.len()
+}
+
+/// Parses the source into a lossless concrete syntax tree (never fails, errors are kept in the tree)
+pub fn parse(src: &str, edition: Edition) -> CstNode {
+    let mut tokens = lex(src, edition).into_iter().peekable();
+    let mut children = Vec::new();
+
+    while let Some(token) = tokens.peek() {
+        match &token.kind {
+            // roots (along with their doc comments)
+            CstTokenKind::Token(Token::Main | Token::DocComment(_)) => children.push(CstElement::Node(parse_item(NodeKind::Main, &mut tokens))),
+            CstTokenKind::Token(Token::Func) => children.push(CstElement::Node(parse_item(NodeKind::FuncDef, &mut tokens))),
+            CstTokenKind::Token(Token::Ident(ident)) if ident == "test" => children.push(CstElement::Node(parse_item(NodeKind::FuncDef, &mut tokens))),
+
+            // trivia & stray tokens
+            _ => children.push(parse_element(&mut tokens)),
+        }
+    }
+
+    CstNode { kind: NodeKind::Root, span: 0..src.len(), children }
+}
+
+type Tokens = std::iter::Peekable<std::vec::IntoIter<CstToken>>;
+
+/// Parses a root item up until (and including) it's body block
+fn parse_item(mut kind: NodeKind, tokens: &mut Tokens) -> CstNode {
+    let mut children = Vec::new();
+    let pos = tokens.peek().map(|token| token.span.start).unwrap_or_default();
+
+    while let Some(token) = tokens.peek() {
+        match &token.kind {
+            // the kind of the item is only known after it's doc comments
+            CstTokenKind::Token(Token::Main) => kind = NodeKind::Main,
+            CstTokenKind::Token(Token::Func) => kind = NodeKind::FuncDef,
+
+            // the body of the item is the end of it
+            CstTokenKind::Token(Token::LBrace) => {
+                children.push(parse_element(tokens));
+                break;
+            },
+
+            _ => (),
+        }
+
+        children.push(parse_element(tokens));
+    }
+
+    CstNode::new(kind, children, pos)
+}
+
+/// Parses either a single token or a delimited group
+fn parse_element(tokens: &mut Tokens) -> CstElement {
+    let token = tokens.next().unwrap();
+
+    let (kind, close) = match token.kind {
+        CstTokenKind::Token(Token::LParen) => (NodeKind::Parens, Token::RParen),
+        CstTokenKind::Token(Token::LBracket) => (NodeKind::Brackets, Token::RBracket),
+        CstTokenKind::Token(Token::LBrace) => return CstElement::Node(parse_block(token, tokens)),
+        _ => return CstElement::Token(token),
+    };
+
+    // parse the group up until the closing token (or the end of the file)
+    let pos = token.span.start;
+    let mut children = vec![CstElement::Token(token)];
+    while let Some(token) = tokens.peek() {
+        let closed = token.kind == CstTokenKind::Token(close.clone());
+        children.push(parse_element(tokens));
+        if closed { break }
+    }
+
+    CstElement::Node(CstNode::new(kind, children, pos))
+}
+
+/// Parses a block and splits it into statements (given the `{` token)
+fn parse_block(lbrace: CstToken, tokens: &mut Tokens) -> CstNode {
+    let pos = lbrace.span.start;
+    let mut children = vec![CstElement::Token(lbrace)];
+    let mut stmt = Vec::new();
+    let mut trivia = Vec::new();
+
+    while let Some(token) = tokens.peek() {
+        match &token.kind {
+            // trivia only becomes part of a statement if it's between the statement's tokens
+            CstTokenKind::Trivia(_) => trivia.push(parse_element(tokens)),
+
+            // the end of a statement (or block)
+            CstTokenKind::Token(tok @ (Token::SemiColon | Token::RBrace)) => {
+                let end = *tok == Token::RBrace;
+                if !stmt.is_empty() {
+                    children.push(CstElement::Node(CstNode::new(NodeKind::Stmt, std::mem::take(&mut stmt), pos)));
+                }
+                children.append(&mut trivia);
+                children.push(parse_element(tokens));
+                if end { break }
+            },
+
+            // part of a statement
+            _ => {
+                if stmt.is_empty() {
+                    children.append(&mut trivia);
+                } else {
+                    stmt.append(&mut trivia);
+                }
+                stmt.push(parse_element(tokens));
+            },
+        }
+    }
+
+    // unclosed block
+    if !stmt.is_empty() {
+        children.push(CstElement::Node(CstNode::new(NodeKind::Stmt, stmt, pos)));
+    }
+    children.append(&mut trivia);
+
+    CstNode::new(NodeKind::Block, children, pos)
+}
@@ -0,0 +1,208 @@
+//! A step debugger for programs run by the interpreter, pausing before statements to inspect variables by their names in the source
+
+use std::{collections::{BTreeSet, HashMap}, io::{BufRead, Write}};
+use super::{cst::{self, CstElement, CstNode, CstTokenKind, NodeKind}, edition::Edition, error::line_column, interpreter::{Frame, Hook, RuntimeError, Value}};
+
+/// The commands understood by the debugger (shown by `help`)
+const HELP: &str = "\
+commands:
+  break <line>   (b)   pause before the statements on a line
+  delete <line>  (d)   remove the breakpoint on a line
+  continue       (c)   run until the next breakpoint
+  next           (n)   run the next statement (stepping over function calls)
+  step           (s)   run the next statement (stepping into function calls)
+  out            (o)   run until the current function returns
+  print <name>   (p)   print a variable that's visible from the current statement
+  locals         (l)   print every variable that's visible from the current statement
+  backtrace      (bt)  print the functions that are running
+  quit           (q)   stop the program
+an empty line repeats the previous command";
+
+/// When the debugger pauses next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Only at breakpoints
+    Continue,
+    /// Before the next statement
+    Step,
+    /// Before the next statement that's not within a deeper function call
+    Next { depth: usize },
+    /// Before the next statement after the function at the depth returns
+    Out { depth: usize },
+}
+
+/// A hook that pauses the interpreter at breakpoints & steps, reading commands from the program's input and printing to `console`
+pub struct Debugger<'a, O: Write> {
+    src: &'a str,
+    /// The name of the source file (for printing locations)
+    src_id: &'a str,
+    /// The (one-based) lines that have breakpoints
+    breakpoints: BTreeSet<usize>,
+    /// The (one-based) lines that statements start on (so breakpoints can be warned about if they'd never be hit)
+    stmt_lines: BTreeSet<usize>,
+    mode: Mode,
+    /// The line & start of the previous statement, so breakpoints are only hit when a line is entered, not for every statement on it
+    /// (jumping back to a statement on the same line, like the next iteration of a one-line loop, enters it again)
+    last_stmt: Option<(usize, usize)>,
+    last_command: String,
+    console: O,
+}
+
+impl<'a, O: Write> Debugger<'a, O> {
+    /// Creates a debugger that pauses before the first statement (or only at the `breakpoints` if there are any)
+    pub fn new(src: &'a str, src_id: &'a str, edition: Edition, breakpoints: impl IntoIterator<Item = usize>, console: O) -> Self {
+        let breakpoints = breakpoints.into_iter().collect::<BTreeSet<_>>();
+        let mut stmt_lines = BTreeSet::new();
+        find_stmt_lines(src, &cst::parse(src, edition), &mut stmt_lines);
+
+        Self {
+            src,
+            src_id,
+            mode: if breakpoints.is_empty() { Mode::Step } else { Mode::Continue },
+            breakpoints,
+            stmt_lines,
+            last_stmt: None,
+            last_command: String::new(),
+            console,
+        }
+    }
+
+    /// The breakpoints on lines without any statements (which are never hit)
+    pub fn unreachable_breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.difference(&self.stmt_lines).copied()
+    }
+
+    /// If the debugger should pause before the statement starting at `start` on `line`
+    fn should_pause(&self, line: usize, start: usize, depth: usize) -> bool {
+        let entered = match self.last_stmt {
+            Some((last_line, last_start)) => last_line != line || start <= last_start,
+            None => true,
+        };
+        let breakpoint = self.breakpoints.contains(&line) && entered;
+        breakpoint || match self.mode {
+            Mode::Continue => false,
+            Mode::Step => true,
+            Mode::Next { depth: next_depth } => depth <= next_depth,
+            Mode::Out { depth: out_depth } => depth < out_depth,
+        }
+    }
+
+    /// Prints where the program is paused
+    fn print_location(&mut self, frame: &Frame, line: usize) -> std::io::Result<()> {
+        let procedure = frame.procedure.as_deref().unwrap_or("main");
+        let src_line = self.src.lines().nth(line - 1).unwrap_or_default();
+        writeln!(self.console, "{}:{line} (in {procedure})", self.src_id)?;
+        writeln!(self.console, "{line:>5} | {}", src_line.trim_end())
+    }
+
+    /// Reads & runs commands until one of them resumes the program
+    fn prompt(&mut self, stack: &[Frame], vars: &HashMap<String, Value>, commands: &mut dyn BufRead) -> Result<(), RuntimeError> {
+        let frame = stack.last().expect("there's always a running procedure");
+        loop {
+            write!(self.console, "(debug) ")?;
+            self.console.flush()?;
+
+            // the end of the commands stops the program, as it can't be resumed
+            let mut line = String::new();
+            if commands.read_line(&mut line)? == 0 {
+                writeln!(self.console)?;
+                return Err(RuntimeError::Quit);
+            }
+
+            // an empty line repeats the previous command (handy for stepping)
+            let line = match line.trim() {
+                "" => self.last_command.clone(),
+                line => line.to_string(),
+            };
+            self.last_command = line.clone();
+
+            let (command, arg) = line.split_once(char::is_whitespace)
+                .map(|(command, arg)| (command, arg.trim()))
+                .unwrap_or((&line, ""));
+            match command {
+                "" => (),
+                "c" | "continue" => { self.mode = Mode::Continue; return Ok(()) },
+                "s" | "step" => { self.mode = Mode::Step; return Ok(()) },
+                "n" | "next" => { self.mode = Mode::Next { depth: stack.len() }; return Ok(()) },
+                "o" | "out" => { self.mode = Mode::Out { depth: stack.len() }; return Ok(()) },
+                "q" | "quit" => return Err(RuntimeError::Quit),
+
+                "b" | "break" => match self.line_arg(arg) {
+                    Some(line) => {
+                        self.breakpoints.insert(line);
+                        match self.stmt_lines.contains(&line) {
+                            true => writeln!(self.console, "breakpoint set on line {line}")?,
+                            false => writeln!(self.console, "breakpoint set on line {line} (but no statements start on it, so it won't be hit)")?,
+                        }
+                    },
+                    None => writeln!(self.console, "expected a line number between 1 and {}", self.src.lines().count())?,
+                },
+                "d" | "delete" => match self.line_arg(arg) {
+                    Some(line) if self.breakpoints.remove(&line) => writeln!(self.console, "breakpoint removed from line {line}")?,
+                    Some(line) => writeln!(self.console, "there's no breakpoint on line {line}")?,
+                    None => writeln!(self.console, "expected a line number between 1 and {}", self.src.lines().count())?,
+                },
+
+                "p" | "print" => match frame.visible_vars(vars).get(arg) {
+                    Some(value) => writeln!(self.console, "{arg} = {}", value.render(true))?,
+                    None => writeln!(self.console, "no variable named `{arg}` is visible here (it may not be declared yet)")?,
+                },
+                "l" | "locals" => {
+                    let visible = frame.visible_vars(vars);
+                    if visible.is_empty() {
+                        writeln!(self.console, "no variables are visible here")?;
+                    }
+                    for (name, value) in visible {
+                        writeln!(self.console, "{name} = {}", value.render(true))?;
+                    }
+                },
+                "bt" | "backtrace" => {
+                    for (i, frame) in stack.iter().rev().enumerate() {
+                        let (line, _) = line_column(self.src, frame.span.start);
+                        writeln!(self.console, "#{i} {} at {}:{line}", frame.procedure.as_deref().unwrap_or("main"), self.src_id)?;
+                    }
+                },
+                "h" | "help" => writeln!(self.console, "{HELP}")?,
+                command => writeln!(self.console, "unknown command `{command}` (try `help`)")?,
+            }
+        }
+    }
+
+    /// Parses the line number argument of a command (only lines within the source are valid)
+    fn line_arg(&self, arg: &str) -> Option<usize> {
+        arg.parse::<usize>().ok().filter(|line| (1..=self.src.lines().count()).contains(line))
+    }
+}
+
+impl<O: Write> Hook for Debugger<'_, O> {
+    fn before_stmt(&mut self, stack: &[Frame], vars: &HashMap<String, Value>, input: &mut dyn BufRead) -> Result<(), RuntimeError> {
+        let frame = stack.last().expect("there's always a running procedure");
+        let (line, _) = line_column(self.src, frame.span.start);
+
+        let pause = self.should_pause(line, frame.span.start, stack.len());
+        self.last_stmt = Some((line, frame.span.start));
+        if !pause {
+            return Ok(());
+        }
+
+        self.print_location(frame, line)?;
+        self.prompt(stack, vars, input)
+    }
+}
+
+/// Finds the (one-based) lines that the statements within a node start on
+fn find_stmt_lines(src: &str, node: &CstNode, lines: &mut BTreeSet<usize>) {
+    if node.kind == NodeKind::Stmt {
+        // statements start at their first token that isn't trivia
+        let start = node.tokens().into_iter().find(|token| !matches!(token.kind, CstTokenKind::Trivia(_)));
+        if let Some(start) = start {
+            lines.insert(line_column(src, start.span.start).0);
+        }
+    }
+
+    for child in node.children.iter() {
+        if let CstElement::Node(child) = child {
+            find_stmt_lines(src, child, lines);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Language editions, which allow for breaking changes to the language without breaking older programs
+
+use std::fmt::Display;
+use logos::Logos;
+use crate::lang::{token::Token, Spanned};
+
+/// An edition of the scrapile language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Edition {
+    /// The original edition, where `-` may be a part of identifiers (kebab-case), so `a-b` is a single identifier
+    #[default]
+    #[value(name = "2023")]
+    E2023,
+    /// `-` is always lexed as an operator, so `a-b` is a subtraction
+    #[value(name = "2024")]
+    E2024,
+}
+
+impl Edition {
+    /// The latest edition of the language
+    pub const LATEST: Edition = Edition::E2024;
+
+    /// Whether `-` may be lexed as a part of identifiers
+    pub fn kebab_idents(self) -> bool {
+        self == Edition::E2023
+    }
+}
+
+impl Display for Edition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Edition::E2023 => write!(f, "2023"),
+            Edition::E2024 => write!(f, "2024"),
+        }
+    }
+}
+
+/// Finds every identifier (and builtin-func) in the source that would change meaning when migrating to the latest edition
+pub fn migration_idents(src: &str) -> Vec<Spanned<String>> {
+    Token::lexer_with_extras(src, Edition::E2023.into())
+        .spanned()
+        .filter_map(|(token, span)| match token {
+            Ok(Token::Ident(ident)) if ident.contains('-') => Some((ident, span)),
+            Ok(Token::BuiltinFunc(ident)) if ident.contains('-') => Some((format!("{ident}!"), span)),
+            _ => None,
+        })
+        .collect()
+}
@@ -1,7 +1,258 @@
 pub mod parser;
 pub mod typed;
+pub mod warning;
+
+use ariadne::{Color, Config, Report, ReportKind, Source};
+use clap::ValueEnum;
+use json::{object, JsonValue};
+use ketchup::Span;
 
 pub trait Reportable {
+    /// A stable code that identifies the kind of error (like `E1018`)
+    fn code(&self) -> &'static str;
+
+    /// Describes the error as a structured diagnostic (without it's code)
+    fn describe(&self) -> Diagnostic;
+
+    /// Describes the error as a structured diagnostic
+    fn diagnostic(&self) -> Diagnostic {
+        self.describe().with_code(self.code())
+    }
+
     /// Prints the error to stderr
-    fn report(&self, src_id: &str, src: &str);
+    fn report(&self, src_id: &str, src: &str, config: &ReportConfig) {
+        self.diagnostic().report(src_id, src, config);
+    }
+}
+
+/// The format that diagnostics are printed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    /// Rich reports with the source code & labels
+    #[default]
+    Human,
+    /// A single line per diagnostic (`file:line:column: severity[code]: message`)
+    Short,
+    /// A json object per line, with the code, severity, message, labelled spans, help & notes
+    Json,
+}
+
+/// How diagnostics are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportConfig {
+    pub format: MessageFormat,
+    /// If the reports are coloured
+    pub color: bool,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self { format: MessageFormat::Human, color: true }
+    }
+}
+
+/// How severe a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The role of a label within a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelKind {
+    /// The location of the problem itself
+    Primary,
+    /// Additional context for the problem
+    Context,
+    /// A related location worth noting
+    Note,
+}
+
+/// A labelled location in the source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub kind: LabelKind,
+    pub span: Span,
+    pub message: String,
+}
+
+/// A structured description of an error or warning (what gets shown when it's reported)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// A stable code that identifies the kind of diagnostic (like `E1018`)
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Creates an error diagnostic with a primary label
+    pub fn error(message: impl Into<String>, span: Span, label: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            code: None,
+            message: message.into(),
+            labels: vec![Label { kind: LabelKind::Primary, span, message: label.into() }],
+            help: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Creates a warning diagnostic without any labels
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            code: None,
+            message: message.into(),
+            labels: Vec::new(),
+            help: None,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Sets the code of the diagnostic
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Adds a label to the diagnostic
+    pub fn with_label(mut self, kind: LabelKind, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { kind, span, message: message.into() });
+        self
+    }
+
+    /// Adds a context label to the diagnostic
+    pub fn with_context(self, span: Span, message: impl Into<String>) -> Self {
+        self.with_label(LabelKind::Context, span, message)
+    }
+
+    /// Sets the help message of the diagnostic
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Adds a note to the diagnostic
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// The span of the primary label (or the first label if there isn't one)
+    pub fn primary_span(&self) -> Span {
+        self.labels.iter()
+            .find(|label| label.kind == LabelKind::Primary)
+            .or(self.labels.first())
+            .map(|label| label.span.clone())
+            .unwrap_or(0..0)
+    }
+
+    /// Prints the diagnostic to stderr
+    pub fn report(&self, src_id: &str, src: &str, config: &ReportConfig) {
+        match config.format {
+            MessageFormat::Human => (),
+            MessageFormat::Json => return eprintln!("{}", self.to_json(src_id, src).dump()),
+            MessageFormat::Short => {
+                let severity = match self.code {
+                    Some(code) => format!("{}[{code}]", self.severity),
+                    None => self.severity.to_string(),
+                };
+                match self.labels.is_empty() {
+                    true => eprintln!("{src_id}: {severity}: {}", self.message),
+                    false => {
+                        let (line, column) = line_column(src, self.primary_span().start);
+                        eprintln!("{src_id}:{line}:{column}: {severity}: {}", self.message);
+                    },
+                }
+                return;
+            },
+        }
+
+        let (kind, primary_color) = match self.severity {
+            Severity::Error => (ReportKind::Error, Color::Red),
+            Severity::Warning => (ReportKind::Warning, Color::Yellow),
+        };
+
+        let mut report = Report::build(kind, src_id, 10)
+            .with_config(Config::default().with_color(config.color))
+            .with_message(&self.message)
+            .with_labels(self.labels.iter().map(|label| {
+                ariadne::Label::new((src_id, label.span.clone()))
+                    .with_message(&label.message)
+                    .with_color(match label.kind {
+                        LabelKind::Primary => primary_color,
+                        LabelKind::Context => Color::BrightBlue,
+                        LabelKind::Note => Color::Yellow,
+                    })
+            }));
+
+        if let Some(code) = self.code {
+            report = report.with_code(code);
+        }
+        if let Some(help) = &self.help {
+            report = report.with_help(help);
+        }
+        for note in self.notes.iter() {
+            report = report.with_note(note);
+        }
+
+        report
+            .finish()
+            .eprint((src_id, Source::from(src)))
+            .unwrap();
+    }
+
+    /// The diagnostic as a json object (lines & columns are one-based, byte offsets are zero-based and columns are counted in chars)
+    pub fn to_json(&self, src_id: &str, src: &str) -> JsonValue {
+        let labels = self.labels.iter().map(|label| {
+            let (line_start, column_start) = line_column(src, label.span.start);
+            let (line_end, column_end) = line_column(src, label.span.end);
+
+            object! {
+                kind: match label.kind {
+                    LabelKind::Primary => "primary",
+                    LabelKind::Context => "context",
+                    LabelKind::Note => "note",
+                },
+                message: label.message.as_str(),
+                byte_start: label.span.start,
+                byte_end: label.span.end,
+                line_start: line_start,
+                column_start: column_start,
+                line_end: line_end,
+                column_end: column_end,
+            }
+        }).collect::<Vec<_>>();
+
+        object! {
+            file: src_id,
+            code: self.code,
+            severity: self.severity.to_string(),
+            message: self.message.as_str(),
+            labels: labels,
+            help: self.help.as_deref(),
+            notes: self.notes.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// The (one-based) line & column of a byte offset in the source
+pub fn line_column(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset.min(src.len())];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
 }
@@ -1,6 +1,5 @@
-use ariadne::{Color, Label, Report, ReportKind, Source};
 use ketchup::{error::KError, Span};
-use super::Reportable;
+use super::{Diagnostic, Reportable};
 
 /// Parsing errors for scrapile
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -9,6 +8,31 @@ pub enum Error {
     #[default]
     UnexpectedCharacter,
 
+    /// Occurs when a string literal is never closed
+    UnterminatedString,
+    /// Occurs when a string literal contains an unknown or malformed escape sequence
+    InvalidEscape {
+        /// The location of the escape sequence
+        escape_span: Span,
+    },
+
+    /// Occurs when a block comment is never closed
+    UnterminatedComment,
+    /// Occurs when a doc comment isn't placed before a root (like `main` or `fn`)
+    DanglingDocComment,
+
+    /// Occurs when a number literal has no digits (like `0x` or `1e`)
+    ExpectedDigits,
+    /// Occurs when a hexadecimal, octal or binary literal contains a digit that is invalid for it's radix
+    InvalidDigit {
+        /// The location of the invalid digit
+        digit_span: Span,
+        /// The radix of the number literal
+        radix: u32,
+    },
+    /// Occurs when a number literal is too large to be represented
+    NumberOverflow,
+
     /// Occurs when a statement is expected but not found
     ExpectedStmt,
     /// Occurs when a expression is expected but not found
@@ -117,11 +141,54 @@ pub enum Error {
 }
 
 impl Reportable for KError<Error> {
-    fn report(&self, src_id: &str, src: &str) {
+    fn code(&self) -> &'static str {
         use KError as K;
         use Error as E;
 
-        let report = Report::build(ReportKind::Error, src_id, 10);
+        // codes are stable, so new errors must always get new codes
+        match self {
+            K::DoubleSpaceConflict { .. } => "E0001",
+            K::UnexpectedOper { .. } => "E0002",
+            K::ExpectedOper { .. } => "E0003",
+
+            K::Other(_, other) => match other {
+                E::UnexpectedCharacter => "E0004",
+                E::UnterminatedString => "E0005",
+                E::InvalidEscape { .. } => "E0006",
+                E::UnterminatedComment => "E0007",
+                E::DanglingDocComment => "E0008",
+                E::ExpectedDigits => "E0009",
+                E::InvalidDigit { .. } => "E0010",
+                E::NumberOverflow => "E0011",
+                E::ExpectedStmt => "E0012",
+                E::ExpectedExpr => "E0013",
+                E::ExpectedRoot => "E0014",
+                E::ExpectedType => "E0015",
+                E::UnclosedParentheses { .. } => "E0016",
+                E::UnclosedBrace { .. } => "E0017",
+                E::UnclosedBrackets { .. } => "E0018",
+                E::ExpectedCommaOrRParen { .. } => "E0019",
+                E::ExpectedSemiOrRBrace { .. } => "E0020",
+                E::ExpectedCommaOrRBracket { .. } => "E0021",
+                E::ExpectedCallLParen { .. } => "E0022",
+                E::ExpectedBlockForMain { .. } => "E0023",
+                E::ExpectedMutOrIdent { .. } => "E0024",
+                E::ExpectedColonOrEQ { .. } => "E0025",
+                E::ExpectedEQ { .. } => "E0026",
+                E::ExpectedIdent { .. } => "E0027",
+                E::ExpectedCondLParen { .. } => "E0028",
+                E::ExpectedFnIdent { .. } => "E0029",
+                E::ExpectedFnParamIdent { .. } => "E0030",
+                E::ExpectedFnParamColon { .. } => "E0031",
+                E::ExpectedFnRetrnType { .. } => "E0032",
+                E::ExpectedFnBody { .. } => "E0033",
+            },
+        }
+    }
+
+    fn describe(&self) -> Diagnostic {
+        use KError as K;
+        use Error as E;
 
         let (msg, span, label, ctx_span, ctx_label) = match self.clone() {
             K::DoubleSpaceConflict { ctx_span, span } => ("expected an expression", span, "found this instead", ctx_span, "expected an expr as an input"),
@@ -130,6 +197,17 @@ impl Reportable for KError<Error> {
 
             K::Other(span, other) => match other {
                 E::UnexpectedCharacter => ("unexpected or invalid character", span.clone(), "unexpected character", span, "consider removing this"),
+                E::UnterminatedString => ("unterminated string literal", span.start..span.start+1, "string literal starts here", span, "expected a closing `\"` before the end of the file"),
+                E::InvalidEscape { escape_span } => ("invalid escape sequence in string literal", escape_span, "unknown or malformed escape sequence", span, "in this string literal (valid escapes include `\\n`, `\\t`, `\\\"`, `\\\\` and `\\u{...}`)"),
+                E::UnterminatedComment => ("unterminated block comment", span.start..span.start+2, "block comment starts here", span, "expected a closing `*/` before the end of the file"),
+                E::DanglingDocComment => ("doc comment doesn't document anything", span.clone(), "this doc comment", span, "doc comments must be placed before a `fn` or `main`; consider using `//` instead"),
+                E::ExpectedDigits => ("malformed number literal", span.clone(), "expected digits in this number literal", span, "consider adding digits, like `0x1f` or `1e3`"),
+                E::InvalidDigit { digit_span, radix } => ("invalid digit in number literal", digit_span, match radix {
+                    16 => "not a valid hexadecimal digit",
+                    8 => "not a valid octal digit",
+                    _ => "not a valid binary digit",
+                }, span, "in this number literal"),
+                E::NumberOverflow => ("number literal is too large", span.clone(), "this number literal overflows", span, "consider using a smaller number or `Infinity`"),
                 E::ExpectedStmt => ("expected statement", span.clone(), "found this instead", span, "consider removing this or inserting a statement"), // assuming it's an error caused by `;;`
                 E::ExpectedExpr => ("expected an expression", span.clone(), "found this instead", span, "consider removing this or inserting an expression"),
                 E::ExpectedRoot => ("expected root token", span.clone(), "expected a root token like `main` or `fn ...`", span, "consider wrapping this in a `main { ... }` or function"),
@@ -160,20 +238,7 @@ impl Reportable for KError<Error> {
             },
         };
 
-        report
-            .with_message(msg)
-            .with_label(
-                Label::new((src_id, span))
-                    .with_message(label)
-                    .with_color(Color::Red),
-            )
-            .with_label(
-                Label::new((src_id, ctx_span))
-                    .with_message(ctx_label)
-                    .with_color(Color::BrightBlue),
-            )
-            .finish()
-            .eprint((src_id, Source::from(src)))
-            .unwrap();
+        Diagnostic::error(msg, span, label)
+            .with_context(ctx_span, ctx_label)
     }
 }
@@ -1,7 +1,6 @@
-use ariadne::{Color, Label, Report, ReportKind, Source};
 use ketchup::Span;
-use crate::lang::typed::types::Type;
-use super::Reportable;
+use crate::lang::typed::{builtin::BUILTINS, types::Type};
+use super::{Diagnostic, LabelKind, Reportable};
 
 /// Type errors for scrapile
 #[derive(Debug, PartialEq, Eq)]
@@ -73,6 +72,12 @@ pub enum Error {
 
         /// The span of the builtin function call
         call_span: Span,
+
+        /// A similarly named builtin function (if there is one)
+        similar: Option<String>,
+
+        /// If there is a user-defined function with the same identifier
+        is_func: bool,
     },
 
     /// Occurs when you try to call a function that doesn't exist
@@ -85,6 +90,12 @@ pub enum Error {
 
         /// The span of the function call
         call_span: Span,
+
+        /// A similarly named function (if there is one)
+        similar: Option<String>,
+
+        /// If there is a builtin function with the same identifier
+        is_builtin: bool,
     },
 
     /// Occurs when a function call's argument is of the wrong type
@@ -97,10 +108,10 @@ pub enum Error {
         call_span: Span,
         /// The span of the call's argument
         arg_span: Span,
-        /// The type of the argument expr
-        arg_type: Type,
+        /// The type of the argument expr (boxed, so the error stays small)
+        arg_type: Box<Type>,
         /// The type of the parameter
-        param_type: Type,
+        param_type: Box<Type>,
     },
 
     /// Occurs when you try to run a builtin function with too many arguments
@@ -175,6 +186,8 @@ pub enum Error {
     TypeNotFound {
         /// The span of the type being used
         span: Span,
+        /// A similarly named type (if there is one)
+        similar: Option<String>,
     },
 
     /// Occurs when you try to assign a value of the wrong type to a variable
@@ -205,6 +218,8 @@ pub enum Error {
     VarNotFound {
         /// The span of the variable use
         span: Span,
+        /// A similarly named variable in scope (if there is one)
+        similar: Option<String>,
     },
 
     /// Occurs when you try to assign to an immutable variable
@@ -248,13 +263,108 @@ pub enum Error {
         /// The span of the builtin-call
         call_span: Span,
     },
+
+    /// Occurs when the format string of a `format` or `println` builtin-function call isn't a string literal
+    FormatNotLiteral {
+        /// The span of the format string
+        span: Span,
+        /// The span of the builtin-call
+        call_span: Span,
+    },
+
+    /// Occurs when the amount of `{}` placeholders in a format string doesn't match the amount of arguments
+    FormatArgsAmount {
+        /// The span of the format string
+        fmt_span: Span,
+        /// The span of the builtin-call
+        call_span: Span,
+        /// The amount of placeholders in the format string
+        placeholders: usize,
+        /// The amount of arguments given
+        given_amount: usize,
+    },
+
+    /// Occurs when there is a `{` or `}` in a format string that isn't part of a placeholder or escaped
+    FormatUnmatchedBrace {
+        /// The span of the format string
+        span: Span,
+        /// The span of the builtin-call
+        call_span: Span,
+    },
+
+    /// Occurs when a test function has parameters or doesn't return `nil`
+    InvalidTest {
+        /// The span of the invalid parameter or return type
+        span: Span,
+        /// The span of the test function
+        test_span: Span,
+    },
+
+    /// Occurs when a main procedure or test function is defined in the repl (only functions can be)
+    ReplRoot {
+        /// The span of the main procedure or test function
+        span: Span,
+    },
+
+    /// Occurs when `list_sort` is called on something other than a list of numbers or strings
+    UnsortableList {
+        /// The span of the argument
+        span: Span,
+        /// The type of the argument
+        arg_type: Type,
+        /// The span of the builtin-call
+        call_span: Span,
+    },
+
+    /// Occurs when a list contains other lists (which scratch lists can't)
+    NestedList {
+        /// The span of the list or type annotation
+        span: Span,
+        /// The type of the list
+        list_type: Type,
+    },
 }
 
 impl Reportable for Error {
-    fn report(&self, src_id: &str, src: &str) {
+    fn code(&self) -> &'static str {
         use Error as E;
 
-        let report = Report::build(ReportKind::Error, src_id, 10);
+        // codes are stable, so new errors must always get new codes
+        match self {
+            E::ArithmeticNonNumber { .. } => "E1001",
+            E::ConcatNonString { .. } => "E1002",
+            E::NotBoolean { .. } => "E1003",
+            E::OperationTypeMismatch { .. } => "E1004",
+            E::BuiltinNotFound { .. } => "E1005",
+            E::FuncNotFound { .. } => "E1006",
+            E::FuncCallTypeMismatch { .. } => "E1007",
+            E::BuiltinManyArgs { .. } => "E1008",
+            E::BuiltinLittleArgs { .. } => "E1009",
+            E::CallArgsAmount { .. } => "E1010",
+            E::BuiltinWrongType { .. } => "E1011",
+            E::MultipleMain { .. } => "E1012",
+            E::MultipleFunc { .. } => "E1013",
+            E::NoMain => "E1014",
+            E::TypeNotFound { .. } => "E1015",
+            E::VarTypeMismatch { .. } => "E1016",
+            E::RetrnTypeMismatch { .. } => "E1017",
+            E::VarNotFound { .. } => "E1018",
+            E::AssignToImmutable { .. } => "E1019",
+            E::ListElementTypeMismatch { .. } => "E1020",
+            E::NonBoolCond { .. } => "E1021",
+            E::BuiltinArgTypeMismatch { .. } => "E1022",
+            E::FormatNotLiteral { .. } => "E1023",
+            E::FormatArgsAmount { .. } => "E1024",
+            E::FormatUnmatchedBrace { .. } => "E1025",
+            E::InvalidTest { .. } => "E1026",
+            E::ReplRoot { .. } => "E1027",
+            E::UnsortableList { .. } => "E1028",
+            E::NestedList { .. } => "E1029",
+        }
+    }
+
+    fn describe(&self) -> Diagnostic {
+        use Error as E;
 
         let (msg, span, label, ctx_span, ctx_label) = match self {
             E::ArithmeticNonNumber { oper_span, oper_type, value_span, value_type } => ("cannot perform mathmatical operations on non-number types", oper_span, format!("cannot perform an {oper_type} operation an expr of type `{value_type}`"), value_span, format!("expected an expr of type `num`, instead found an expr of type `{value_type}`")),
@@ -266,116 +376,81 @@ impl Reportable for Error {
             E::BuiltinWrongType { call_span, expected, arg_type, arg_span } => ("builtin function's argument is of an incorrect type", arg_span, format!("expected an expr of type `{expected}`, instead found an expr of type `{arg_type}`",), call_span, "occured in this builtin-func call".to_string()),
             E::MultipleMain { first_span, additional_span } => ("multiple main procedure definitions are not allowed", additional_span, "unexpected second main procedure definition".to_string(), first_span, "first main procedure defined here".to_string()),
             E::MultipleFunc { first_span, additional_span } => ("function was defined multiple times", additional_span, "unexpected second definition".to_string(), first_span, "function was defined first here".to_string()),
-            E::TypeNotFound { span } => ("type not found", span, "this type was not found in the project".to_string(), span, "it may be a typo or otherwise consider adding it or importing it".to_string()),
-            E::FuncNotFound { ident_span, ident, call_span } => ("called function not found in scope", ident_span, format!("no function called '{ident}' found"), call_span, "in this function call".to_string()),
             E::VarTypeMismatch { span, type_span, expr_type, var_type } => ("variable assigned to with a value of the wrong type", span, format!("this expr is of the wrong type, expected an expr of type `{var_type}`, instead found an expr of type `{expr_type}`"), type_span, format!("variable's type `{var_type}` determined here")),
             E::RetrnTypeMismatch { span, type_span, expr_type, retrn_type } => ("function body-block returns an expr of the wrong type", span, format!("this expr is of the wrong type, expected an expr of type `{retrn_type}`, instead found an expr of type `{expr_type}`"), type_span, format!("function's return-type `{retrn_type}` defined here")),
-            E::VarNotFound { span } => ("variable not found", span, "this variable was not found in the current scope".to_string(), span, "it may be a typo or otherwise consider adding a variable of that name".to_string()),
             E::AssignToImmutable { var_span, span } => ("assignment to an immutable variable", span, "invalid mutation to a variable that is immutable".to_string(), var_span, "variable declared here, consider adding the `mut` keyword after `let` to make it mutable".to_string()),
             E::ListElementTypeMismatch { first_span, first_type, el_span, el_type } => ("list element's type doesn't match the type of the list", el_span, format!("expected an element of type `{first_type}`, instead found an element of type `{el_type}`"), first_span, format!("list is of type `{first_type}` due to the first element's type")),
             E::NonBoolCond { span, expr_type, ctx_span } => ("invalid non-boolean condition for 'if'/'while' statement", span, format!("expr is of type `{expr_type}`, expected an expr of type `bool`"), ctx_span, "part of this 'if' statement".to_string()),
             E::BuiltinArgTypeMismatch { span, arg_type, param_type, call_span } => ("argument to builtin-function call is of the wrong type", span, format!("expected an expr of type `{param_type}`, instead found an expr of type `{arg_type}`"), call_span, "in this builtin-func call".to_string()),
+            E::FormatNotLiteral { span, call_span } => ("format string must be a string literal", span, "expected a string literal like \"x = {}\"".to_string(), call_span, "in this builtin-func call".to_string()),
+            E::FormatArgsAmount { fmt_span, call_span, placeholders, given_amount } => ("format string's placeholders don't match the amount of arguments", call_span, format!("expected {placeholders} arguments, found {given_amount} instead"), fmt_span, format!("format string has {placeholders} `{{}}` placeholders")),
+            E::FormatUnmatchedBrace { span, call_span } => ("invalid format string", span, "unmatched `{` or `}` in format string".to_string(), call_span, "consider escaping braces by doubling them like `{{` or `}}`".to_string()),
+            E::InvalidTest { span, test_span } => ("invalid test function signature", span, "test functions can't take any parameters and must return `nil`".to_string(), test_span, "in this test function".to_string()),
             
-            E::NoMain => {
-                return report
-                    .with_message("no main procedure found")
-                    .with_label(
-                        Label::new((src_id, 0..0))
-                            .with_message("expected a main procedure definition")
-                        .with_color(Color::Red),
-                    )
-                    .with_help("you could try defining a main procedure like so `main { ... }`")
-                    .finish()
-                    .eprint((src_id, Source::from(src)))
-                    .unwrap();
+            E::UnsortableList { span, arg_type, call_span } => return Diagnostic::error("list can't be sorted", span.clone(), format!("expected an expr of type `[num]` or `[str]`, instead found an expr of type `{arg_type}`"))
+                .with_context(call_span.clone(), "in this builtin-func call")
+                .with_help("only lists of numbers or strings can be sorted"),
+
+            E::NestedList { span, list_type } => return Diagnostic::error("lists can't contain other lists", span.clone(), format!("`{list_type}` contains a list of lists"))
+                .with_help("scratch lists can only hold numbers & strings, consider using multiple lists instead"),
+
+            E::ReplRoot { span } => return Diagnostic::error("only functions can be defined in the repl", span.clone(), "main procedures & test functions can't be defined in the repl")
+                .with_help("you could try entering the statements of the body directly instead"),
+
+            E::NoMain => return Diagnostic::error("no main procedure found", 0..0, "expected a main procedure definition")
+                .with_help("you could try defining a main procedure like so `main { ... }`"),
+
+            E::TypeNotFound { span, similar } => return did_you_mean(
+                Diagnostic::error("type not found", span.clone(), "this type was not found in the project")
+                    .with_context(span.clone(), "it may be a typo or otherwise consider adding it or importing it"),
+                similar, "",
+            ),
+
+            E::VarNotFound { span, similar } => return did_you_mean(
+                Diagnostic::error("variable not found", span.clone(), "this variable was not found in the current scope")
+                    .with_context(span.clone(), "it may be a typo or otherwise consider adding a variable of that name"),
+                similar, "",
+            ),
+
+            E::FuncNotFound { ident_span, ident, call_span, similar, is_builtin } => {
+                let diagnostic = Diagnostic::error("called function not found in scope", ident_span.clone(), format!("no function called '{ident}' found"))
+                    .with_context(call_span.clone(), "in this function call");
+                return match is_builtin {
+                    true => diagnostic.with_help(format!("`{ident}` is a builtin-func, add a `!` to call it like `{ident}!(...)`")),
+                    false => did_you_mean(diagnostic, similar, ""),
+                };
             },
 
-            E::BuiltinNotFound { ident_span, ident, call_span } => {
-                return report
-                    .with_message("invalid builtin-function call")
-                    .with_label(
-                        Label::new((src_id, call_span.clone()))
-                            .with_message("this builtin-func call is invalid")
-                            .with_color(Color::Red),
-                    )
-                    .with_label(
-                        Label::new((src_id, ident_span.clone()))
-                            .with_message(format!("no builtin-func was found with the name '{ident}'"))
-                            .with_color(Color::BrightBlue),
-                    )
-                    .with_help("available builtin-funcs include: 'println', 'as_str', 'input', 'timer', 'panic', 'list_len', 'list_get', 'list_push' and 'list_insert'")
-                    .finish()
-                    .eprint((src_id, Source::from(src)))
-                    .unwrap();
+            E::BuiltinNotFound { ident_span, ident, call_span, similar, is_func } => {
+                let diagnostic = Diagnostic::error("invalid builtin-function call", call_span.clone(), "this builtin-func call is invalid")
+                    .with_context(ident_span.clone(), format!("no builtin-func was found with the name '{ident}'"));
+                let names = BUILTINS.iter().map(|(name, _)| format!("'{name}'")).collect::<Vec<_>>();
+                return match (is_func, similar) {
+                    (true, _) => diagnostic.with_help(format!("`{ident}` is a function, remove the `!` to call it like `{ident}(...)`")),
+                    (false, Some(_)) => did_you_mean(diagnostic, similar, "!"),
+                    (false, None) => diagnostic.with_help(format!("available builtin-funcs include: {} and {}", names[..names.len()-1].join(", "), names[names.len()-1])),
+                };
             },
 
-            E::FuncCallTypeMismatch { func_span, param_span, call_span, arg_span, arg_type, param_type } => {
-                return report
-                    .with_message("function called with an argument of the wrong type")
-                    .with_label(
-                        Label::new((src_id, arg_span.clone()))
-                            .with_message("argument is of the wrong type")
-                            .with_message(format!("expected an argument of type '{param_type}', found an expr of type '{arg_type}'"))
-                            .with_color(Color::Red),
-                    )
-                    .with_label(
-                        Label::new((src_id, call_span.clone()))
-                            .with_message("in this function call")
-                            .with_color(Color::BrightBlue),
-                    )
-                    .with_label(
-                        Label::new((src_id, param_span.clone()))
-                            .with_message("parameter type defined here")
-                            .with_color(Color::Yellow),
-                    )
-                    .with_label(
-                        Label::new((src_id, func_span.clone()))
-                            .with_message("due to this function definition")
-                            .with_color(Color::BrightBlue),
-                    )
-                .finish()
-                .eprint((src_id, Source::from(src)))
-                .unwrap();
-            },
+            E::FuncCallTypeMismatch { func_span, param_span, call_span, arg_span, arg_type, param_type } => return Diagnostic::error("function called with an argument of the wrong type", arg_span.clone(), format!("expected an argument of type '{param_type}', found an expr of type '{arg_type}'"))
+                .with_context(call_span.clone(), "in this function call")
+                .with_label(LabelKind::Note, param_span.clone(), "parameter type defined here")
+                .with_context(func_span.clone(), "due to this function definition"),
 
-            E::OperationTypeMismatch { lhs_span, lhs_type, oper_span, rhs_span, rhs_type } => {
-                return report
-                    .with_message("type mismatch between the left and right sides of an operation")
-                    .with_label(
-                        Label::new((src_id, lhs_span.clone()))
-                            .with_message(format!("expected a `{lhs_type}` due to the type of this expr"))
-                            .with_color(Color::BrightBlue),
-                    )
-                    .with_label(
-                        Label::new((src_id, oper_span.clone()))
-                            .with_message(format!("cannot operate upon two values of different types"))
-                            .with_color(Color::Red),
-                    )
-                    .with_label(
-                        Label::new((src_id, rhs_span.clone()))
-                            .with_message(format!("expected an expr of type `{lhs_type}`, instead found a value of type `{rhs_type}`"))
-                            .with_color(Color::BrightBlue),
-                    )
-                    .finish()
-                    .eprint((src_id, Source::from(src)))
-                    .unwrap();
-            },
+            E::OperationTypeMismatch { lhs_span, lhs_type, oper_span, rhs_span, rhs_type } => return Diagnostic::error("type mismatch between the left and right sides of an operation", oper_span.clone(), "cannot operate upon two values of different types")
+                .with_context(lhs_span.clone(), format!("expected a `{lhs_type}` due to the type of this expr"))
+                .with_context(rhs_span.clone(), format!("expected an expr of type `{lhs_type}`, instead found a value of type `{rhs_type}`")),
         };
 
-        report
-            .with_message(msg)
-            .with_label(
-                Label::new((src_id, span.clone()))
-                    .with_message(label)
-                    .with_color(Color::Red),
-            )
-            .with_label(
-                Label::new((src_id, ctx_span.clone()))
-                    .with_message(ctx_label)
-                    .with_color(Color::BrightBlue),
-            )
-            .finish()
-            .eprint((src_id, Source::from(src)))
-            .unwrap();
+        Diagnostic::error(msg, span.clone(), label)
+            .with_context(ctx_span.clone(), ctx_label)
+    }
+}
+
+/// Adds a "did you mean" help message to a diagnostic (if there's a similarly named identifier)
+fn did_you_mean(diagnostic: Diagnostic, similar: &Option<String>, suffix: &str) -> Diagnostic {
+    match similar {
+        Some(similar) => diagnostic.with_help(format!("did you mean `{similar}{suffix}`?")),
+        None => diagnostic,
     }
 }
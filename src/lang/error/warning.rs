@@ -0,0 +1,156 @@
+use ketchup::Span;
+use crate::lang::{edition::Edition, lint::Lint, typed::types::Type, Spanned};
+use super::{Diagnostic, LabelKind, Reportable};
+
+/// Non-fatal warnings for scrapile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// Occurs when compiling with an older edition and there are identifiers that would change meaning in the latest edition
+    EditionMigration {
+        /// The edition that the program is compiled with
+        edition: Edition,
+        /// The identifiers (and their locations) that would change meaning
+        idents: Vec<Spanned<String>>,
+    },
+
+    /// Occurs when a lint directive (like `#![allow(...)]`) names a lint that doesn't exist
+    UnknownLint {
+        /// The name of the lint
+        name: String,
+        /// The location of the directive
+        span: Span,
+    },
+
+    /// Occurs when a variable is declared but it's value is never read
+    UnusedVariable {
+        /// The identifier of the variable
+        ident: String,
+        /// The location of the variable's identifier
+        span: Span,
+    },
+
+    /// Occurs when a function is defined but never called (outside of itself)
+    UnusedFunction {
+        /// The identifier of the function
+        ident: String,
+        /// The location of the function's identifier
+        span: Span,
+    },
+
+    /// Occurs when a variable is declared `mut` but is never mutated
+    UnusedMut {
+        /// The identifier of the variable
+        ident: String,
+        /// The location of the variable's identifier
+        span: Span,
+    },
+
+    /// Occurs when a variable declaration shadows a variable from an outer scope
+    Shadowing {
+        /// The identifier of the variable
+        ident: String,
+        /// The location of the new variable's identifier
+        span: Span,
+        /// The location of the shadowed variable's identifier
+        shadowed_span: Span,
+    },
+
+    /// Occurs when there is code after a `panic!` that will never run
+    UnreachableCode {
+        /// The location of the unreachable code
+        span: Span,
+        /// The location of the `panic!` call
+        panic_span: Span,
+    },
+
+    /// Occurs when the value of an expression statement is discarded
+    UnusedValue {
+        /// The location of the expression statement
+        span: Span,
+        /// The type of the discarded value
+        value_type: Type,
+    },
+}
+
+impl Warning {
+    /// The lint that controls the warning (if it's a lint)
+    pub fn lint(&self) -> Option<Lint> {
+        use Warning as W;
+
+        match self {
+            W::EditionMigration { .. } | W::UnknownLint { .. } => None,
+            W::UnusedVariable { .. } => Some(Lint::UnusedVariables),
+            W::UnusedFunction { .. } => Some(Lint::UnusedFunctions),
+            W::UnusedMut { .. } => Some(Lint::UnusedMut),
+            W::Shadowing { .. } => Some(Lint::Shadowing),
+            W::UnreachableCode { .. } => Some(Lint::UnreachableCode),
+            W::UnusedValue { .. } => Some(Lint::UnusedValue),
+        }
+    }
+}
+
+impl Reportable for Warning {
+    fn code(&self) -> &'static str {
+        match self {
+            Warning::EditionMigration { .. } => "W0001",
+            Warning::UnknownLint { .. } => "W0002",
+            Warning::UnusedVariable { .. } => "W0003",
+            Warning::UnusedFunction { .. } => "W0004",
+            Warning::UnusedMut { .. } => "W0005",
+            Warning::Shadowing { .. } => "W0006",
+            Warning::UnreachableCode { .. } => "W0007",
+            Warning::UnusedValue { .. } => "W0008",
+        }
+    }
+
+    fn describe(&self) -> Diagnostic {
+        use Warning as W;
+
+        match self {
+            W::EditionMigration { edition, idents } => {
+                // list each identifier only once (in order of appearance)
+                let mut names = Vec::new();
+                for (ident, _) in idents {
+                    let name = format!("`{ident}`");
+                    if !names.contains(&name) { names.push(name) }
+                }
+                let names = names.join(", ");
+
+                let mut diagnostic = Diagnostic::warning(format!("identifiers would change meaning in edition {}: {names}", Edition::LATEST));
+                for (ident, span) in idents {
+                    diagnostic = diagnostic.with_label(LabelKind::Primary, span.clone(), format!("`-` would be lexed as an operator, turning `{ident}` into `{}`", ident.replace('-', " - ")));
+                }
+                diagnostic.with_note(format!("compiled with edition {edition}; consider renaming these identifiers (eg. to snake_case) before switching to edition {} with `--edition` or the project manifest", Edition::LATEST))
+            },
+
+            W::UnknownLint { name, span } => Diagnostic::warning(format!("unknown lint `{name}`"))
+                .with_label(LabelKind::Primary, span.clone(), "in this lint directive")
+                .with_help(format!("available lints include: {}", Lint::ALL.iter().map(|lint| format!("`{}`", lint.name())).collect::<Vec<_>>().join(", "))),
+
+            W::UnusedVariable { ident, span } => Diagnostic::warning(format!("unused variable `{ident}`"))
+                .with_label(LabelKind::Primary, span.clone(), "this variable's value is never read")
+                .with_help(format!("if this is intentional, prefix it with an underscore like `_{ident}`")),
+
+            W::UnusedFunction { ident, span } => Diagnostic::warning(format!("function `{ident}` is never called"))
+                .with_label(LabelKind::Primary, span.clone(), "this function is never called")
+                .with_help(format!("if this is intentional, prefix it with an underscore like `_{ident}`")),
+
+            W::UnusedMut { ident, span } => Diagnostic::warning(format!("variable `{ident}` does not need to be mutable"))
+                .with_label(LabelKind::Primary, span.clone(), "this variable is never mutated")
+                .with_help("remove the `mut` keyword"),
+
+            W::Shadowing { ident, span, shadowed_span } => Diagnostic::warning(format!("variable `{ident}` shadows a variable from an outer scope"))
+                .with_label(LabelKind::Primary, span.clone(), "this declaration shadows the outer variable")
+                .with_context(shadowed_span.clone(), "the outer variable is declared here")
+                .with_help("consider renaming one of the variables"),
+
+            W::UnreachableCode { span, panic_span } => Diagnostic::warning("unreachable code")
+                .with_label(LabelKind::Primary, span.clone(), "this code will never run")
+                .with_context(panic_span.clone(), "as the program always panics here"),
+
+            W::UnusedValue { span, value_type } => Diagnostic::warning("unused value in expression statement")
+                .with_label(LabelKind::Primary, span.clone(), format!("this expr's value of type `{value_type}` is discarded"))
+                .with_help("consider using the value, or removing the statement"),
+        }
+    }
+}
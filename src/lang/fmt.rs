@@ -0,0 +1,501 @@
+//! The source code formatter, which prints the concrete syntax tree in the canonical style (while preserving comments)
+
+use crate::lang::{cst::{self, CstElement, CstNode, CstToken, CstTokenKind, NodeKind, TriviaKind}, edition::Edition, token::Token};
+
+/// The indentation of a single level
+const INDENT: &str = "    ";
+/// The maximum width of a line before lists, tuples & arguments are split over multiple lines
+const MAX_WIDTH: usize = 100;
+
+/// Formats the source code in the canonical style (assumes that the source code is syntactically correct)
+pub fn format(src: &str, edition: Edition) -> String {
+    let root = cst::parse(src, edition);
+    let mut fmt = Formatter::new(src, 0);
+    fmt.root(&root);
+
+    // always end with a single newline
+    let mut out = fmt.out.trim_end().to_string();
+    out.push('\n');
+    out
+}
+
+/// An element of a statement (with whitespace already stripped out)
+enum Item<'a> {
+    Elem(&'a CstElement),
+    /// A comment, along with if it's preceded by a newline
+    Comment(&'a CstToken, bool),
+}
+
+struct Formatter<'a> {
+    src: &'a str,
+    out: String,
+    indent: usize,
+    /// The previous significant token (used for spacing)
+    prev: Option<Token>,
+    /// If the previous token was a unary operator
+    unary: bool,
+    /// If any line breaks were written (excluding those within tokens like multi-line strings)
+    broken: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(src: &'a str, indent: usize) -> Self {
+        Self { src, out: String::new(), indent, prev: None, unary: false, broken: false }
+    }
+
+    /// Writes text to the output (indenting it if it's at the start of a line)
+    fn write(&mut self, text: &str) {
+        if self.at_line_start() {
+            for _ in 0..self.indent { self.out.push_str(INDENT) }
+        }
+        self.out.push_str(text);
+    }
+
+    /// If nothing has been written to the current line yet
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    /// The current column of the output
+    fn column(&self) -> usize {
+        let line = &self.out[self.out.rfind('\n').map(|i| i + 1).unwrap_or(0)..];
+        if line.is_empty() { self.indent * INDENT.len() } else { line.chars().count() }
+    }
+
+    /// Starts a new line (if the current one isn't empty)
+    fn newline(&mut self) {
+        if !self.at_line_start() {
+            let len = self.out.trim_end_matches([' ', '\t']).len();
+            self.out.truncate(len);
+            self.out.push('\n');
+            self.broken = true;
+        }
+        self.prev = None;
+        self.unary = false;
+    }
+
+    /// Makes sure that there is an empty line before the next line
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes a comment (either trailing the current line, or on it's own line)
+    fn comment(&mut self, token: &CstToken, own_line: bool) {
+        let text = self.src[token.span.clone()].trim_end();
+        if own_line {
+            self.newline();
+        } else if !self.at_line_start() {
+            self.out.push(' ');
+        }
+        self.write(text);
+
+        // line comments (and block comments on their own line) always end the line
+        match token.kind {
+            CstTokenKind::Trivia(TriviaKind::BlockComment) if !own_line => self.prev = None,
+            _ => self.newline(),
+        }
+    }
+
+    /// Writes a significant token (along with the spacing before it)
+    fn token(&mut self, token: &CstToken) {
+        let text = self.src[token.span.clone()].trim_end();
+        let CstTokenKind::Token(tok) = &token.kind
+        else {
+            if !self.at_line_start() { self.out.push(' ') }
+            self.write(text);
+            self.prev = None;
+            return;
+        };
+
+        // doc comments are always on their own line
+        if let Token::DocComment(_) = tok {
+            self.newline();
+            self.write(text);
+            self.newline();
+            return;
+        }
+
+        let unary = matches!(tok, Token::Minus | Token::Plus) && !self.prev.as_ref().is_some_and(ends_operand);
+        if !self.at_line_start() && self.prev.as_ref().is_some_and(|prev| !self.unary && needs_space(prev, tok)) {
+            self.out.push(' ');
+        }
+
+        self.write(text);
+        self.prev = Some(tok.clone());
+        self.unary = unary;
+    }
+
+    /// Writes either a token or a node
+    fn element(&mut self, element: &CstElement) {
+        match element {
+            CstElement::Token(token) => self.token(token),
+            CstElement::Node(node) => match node.kind {
+                NodeKind::Block => self.block(node),
+                NodeKind::Parens | NodeKind::Brackets => self.group(node),
+                NodeKind::Stmt => self.stmt(node),
+                NodeKind::Main | NodeKind::FuncDef => self.item(node),
+                NodeKind::Root => self.root(node),
+            },
+        }
+    }
+
+    /// Writes the entire source file
+    fn root(&mut self, node: &CstNode) {
+        let mut newlines = 0;
+        let mut prev_item = false;
+
+        for child in node.children.iter() {
+            match child {
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Trivia(kind), .. }) => match kind {
+                    TriviaKind::Whitespace => newlines += self.src[token.span.clone()].matches('\n').count(),
+                    _ => {
+                        if newlines > 1 || prev_item { self.blank_line() }
+                        self.comment(token, newlines > 0 || self.out.is_empty());
+                        newlines = 0;
+                        prev_item = false;
+                    },
+                },
+
+                CstElement::Node(_) => {
+                    if newlines > 1 || prev_item { self.blank_line() }
+                    self.newline();
+                    self.element(child);
+                    newlines = 0;
+                    prev_item = true;
+                },
+
+                CstElement::Token(token) => {
+                    self.token(token);
+                    newlines = 0;
+                },
+            }
+        }
+    }
+
+    /// Writes a root item (like `main` or a function definition)
+    fn item(&mut self, node: &CstNode) {
+        let mut newline = true;
+
+        for child in node.children.iter() {
+            match child {
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Trivia(kind), .. }) => match kind {
+                    TriviaKind::Whitespace => newline |= self.src[token.span.clone()].contains('\n'),
+                    _ => {
+                        self.comment(token, newline);
+                        newline = false;
+                    },
+                },
+                _ => {
+                    self.element(child);
+                    newline = false;
+                },
+            }
+        }
+    }
+
+    /// Writes a block over multiple lines (one statement per line)
+    fn block(&mut self, node: &CstNode) {
+        let (open, inner, close) = split_group(node);
+
+        // empty blocks
+        if inner.iter().all(|child| matches!(child, CstElement::Token(CstToken { kind: CstTokenKind::Trivia(TriviaKind::Whitespace), .. }))) {
+            self.token(open);
+            if let Some(close) = close { self.write(&self.src[close.span.clone()]) }
+            self.prev = Some(Token::RBrace);
+            self.unary = false;
+            return;
+        }
+
+        self.token(open);
+        self.indent += 1;
+        let mut newlines = 0;
+        let mut first = true;
+
+        for child in inner {
+            match child {
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Trivia(kind), .. }) => match kind {
+                    TriviaKind::Whitespace => newlines += self.src[token.span.clone()].matches('\n').count(),
+                    _ => {
+                        if newlines > 1 && !first { self.blank_line() }
+                        self.comment(token, newlines > 0);
+                        newlines = 0;
+                        first = false;
+                    },
+                },
+
+                // statements always start on a new line
+                CstElement::Node(node) if node.kind == NodeKind::Stmt => {
+                    if newlines > 1 && !first { self.blank_line() }
+                    self.newline();
+                    self.stmt(node);
+                    newlines = 0;
+                    first = false;
+                },
+
+                // semi-colons are placed directly after their statement
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Token(Token::SemiColon), .. }) => {
+                    self.write(&self.src[token.span.clone()]);
+                    self.prev = Some(Token::SemiColon);
+                    newlines = 0;
+                },
+
+                _ => {
+                    self.element(child);
+                    newlines = 0;
+                },
+            }
+        }
+
+        self.indent -= 1;
+        self.newline();
+        if let Some(close) = close { self.write(&self.src[close.span.clone()]) }
+        self.prev = Some(Token::RBrace);
+        self.unary = false;
+    }
+
+    /// Writes a list, tuple or arguments on a single line if it fits, otherwise over multiple lines with one element per line
+    /// (groups with only a single element are never split)
+    fn group(&mut self, node: &CstNode) {
+        let (open, inner, close) = split_group(node);
+        let has_comma = inner.iter().any(is_comma);
+
+        // try to fit the group on a single line
+        let mut single = Formatter::new(self.src, 0);
+        single.group_single_line(node);
+        let width = single.out.lines().next().unwrap_or_default().chars().count();
+        if !has_comma || !single.broken && self.column() + 1 + width <= MAX_WIDTH {
+            self.group_single_line(node);
+            return;
+        }
+
+        // otherwise split it over multiple lines
+        self.token(open);
+        self.indent += 1;
+        let mut in_elem = false;
+        let mut newline = false;
+
+        for child in inner {
+            match child {
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Trivia(kind), .. }) => match kind {
+                    TriviaKind::Whitespace => newline |= self.src[token.span.clone()].contains('\n'),
+                    _ => {
+                        self.comment(token, newline || !in_elem && self.prev.is_none());
+                        newline = false;
+                    },
+                },
+
+                child if is_comma(child) => {
+                    self.write(",");
+                    self.prev = Some(Token::Comma);
+                    in_elem = false;
+                    newline = false;
+                },
+
+                // each element starts on a new line
+                _ => {
+                    if !in_elem { self.newline() }
+                    self.element(child);
+                    in_elem = true;
+                    newline = false;
+                },
+            }
+        }
+
+        // trailing comma
+        if in_elem {
+            self.write(",");
+        }
+
+        self.indent -= 1;
+        self.newline();
+        if let Some(close) = close {
+            self.write(&self.src[close.span.clone()]);
+            if let CstTokenKind::Token(tok) = &close.kind { self.prev = Some(tok.clone()) }
+        }
+    }
+
+    /// Writes a group on a single line (without any trailing commas, unless it only has a single element)
+    fn group_single_line(&mut self, node: &CstNode) {
+        let (open, inner, close) = split_group(node);
+        let elements = inner.iter().filter(|child| !is_trivia(child) && !is_comma(child)).count();
+        let mut newline = false;
+
+        self.token(open);
+        for (i, child) in inner.iter().enumerate() {
+            match child {
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Trivia(kind), .. }) => match kind {
+                    TriviaKind::Whitespace => newline |= self.src[token.span.clone()].contains('\n'),
+                    _ => self.comment(token, newline),
+                },
+
+                // skip trailing commas
+                child if is_comma(child) && elements > 1 && inner[i+1..].iter().all(is_trivia) => (),
+
+                _ => {
+                    self.element(child);
+                    newline = false;
+                },
+            }
+        }
+        if let Some(close) = close { self.token(close) }
+    }
+
+    /// Writes a statement
+    fn stmt(&mut self, node: &CstNode) {
+        // strip the whitespace out of the statement
+        let mut items = Vec::new();
+        let mut newline = false;
+        for child in node.children.iter() {
+            match child {
+                CstElement::Token(token @ CstToken { kind: CstTokenKind::Trivia(kind), .. }) => match kind {
+                    TriviaKind::Whitespace => newline |= self.src[token.span.clone()].contains('\n'),
+                    _ => {
+                        items.push(Item::Comment(token, newline));
+                        newline = false;
+                    },
+                },
+                _ => {
+                    items.push(Item::Elem(child));
+                    newline = false;
+                },
+            }
+        }
+
+        let mut i = 0;
+        while i < items.len() {
+            self.stmt_items(&items, &mut i);
+        }
+    }
+
+    /// Writes a single (possibly nested) statement from a statement's items
+    fn stmt_items(&mut self, items: &[Item], i: &mut usize) {
+        self.comments(items, i);
+
+        match items.get(*i) {
+            // 'if' & 'while' statements
+            Some(Item::Elem(elem)) if is_token(elem, &Token::If) || is_token(elem, &Token::While) => {
+                let is_if = is_token(elem, &Token::If);
+                self.element(elem);
+                *i += 1;
+
+                // condition
+                self.comments(items, i);
+                if let Some(Item::Elem(cond)) = items.get(*i) {
+                    self.element(cond);
+                    *i += 1;
+                }
+
+                let block = self.body(items, i);
+                if !is_if { return }
+
+                // else branch
+                self.comments(items, i);
+                let Some(Item::Elem(elem)) = items.get(*i)
+                else { return };
+                if !is_token(elem, &Token::Else) { return }
+
+                if !block { self.newline() }
+                self.element(elem);
+                *i += 1;
+
+                self.comments(items, i);
+                match items.get(*i) {
+                    Some(Item::Elem(elem)) if is_token(elem, &Token::If) => self.stmt_items(items, i),
+                    _ => { self.body(items, i); },
+                }
+            },
+
+            // any other statement (ends at an `else`)
+            _ => while let Some(item) = items.get(*i) {
+                match item {
+                    Item::Elem(elem) if is_token(elem, &Token::Else) => break,
+                    Item::Elem(elem) => self.element(elem),
+                    Item::Comment(token, newline) => self.comment(token, *newline),
+                }
+                *i += 1;
+            },
+        }
+    }
+
+    /// Writes the body of an 'if', 'else' or 'while' statement, returning if it was a block
+    fn body(&mut self, items: &[Item], i: &mut usize) -> bool {
+        // keep trailing comments on the same line as the condition or `else`
+        while let Some(Item::Comment(token, false)) = items.get(*i) {
+            self.comment(token, false);
+            *i += 1;
+        }
+
+        match items.get(*i) {
+            Some(Item::Elem(CstElement::Node(node))) if node.kind == NodeKind::Block => {
+                self.block(node);
+                *i += 1;
+                true
+            },
+
+            // non-block bodies are placed on their own (indented) line
+            Some(_) => {
+                self.indent += 1;
+                self.newline();
+                self.stmt_items(items, i);
+                self.indent -= 1;
+                false
+            },
+
+            None => false,
+        }
+    }
+
+    /// Writes any comments at the current position of a statement's items
+    fn comments(&mut self, items: &[Item], i: &mut usize) {
+        while let Some(Item::Comment(token, newline)) = items.get(*i) {
+            self.comment(token, *newline);
+            *i += 1;
+        }
+    }
+}
+
+/// Splits a group into it's opening token, inner elements and closing token (if it's closed)
+fn split_group(node: &CstNode) -> (&CstToken, &[CstElement], Option<&CstToken>) {
+    let CstElement::Token(open) = &node.children[0]
+    else { unreachable!("groups always start with their opening token") };
+
+    let close = match node.children.last() {
+        Some(CstElement::Token(token)) if node.children.len() > 1 && matches!(token.kind, CstTokenKind::Token(Token::RParen | Token::RBracket | Token::RBrace)) => Some(token),
+        _ => None,
+    };
+    let end = node.children.len() - close.is_some() as usize;
+
+    (open, &node.children[1..end], close)
+}
+
+/// If the element is a specific token
+fn is_token(element: &CstElement, token: &Token) -> bool {
+    matches!(element, CstElement::Token(CstToken { kind: CstTokenKind::Token(tok), .. }) if tok == token)
+}
+
+fn is_comma(element: &CstElement) -> bool {
+    is_token(element, &Token::Comma)
+}
+
+fn is_trivia(element: &CstElement) -> bool {
+    matches!(element, CstElement::Token(CstToken { kind: CstTokenKind::Trivia(_), .. }))
+}
+
+/// If the token ends an operand (so a following `-` or `+` is a binary operator)
+fn ends_operand(token: &Token) -> bool {
+    matches!(token, Token::Ident(_) | Token::Number(_) | Token::String(_) | Token::Bool(_) | Token::RParen | Token::RBracket | Token::RBrace)
+}
+
+/// If there should be a space between two tokens
+fn needs_space(prev: &Token, next: &Token) -> bool {
+    use Token as T;
+
+    !matches!((prev, next),
+        (_, T::Comma | T::SemiColon | T::RParen | T::RBracket | T::Dot | T::Colon)
+        | (T::LParen | T::LBracket | T::Dot | T::Not, _)
+        | (T::Ident(_) | T::BuiltinFunc(_), T::LParen)
+    )
+}
@@ -0,0 +1,621 @@
+//! An interpreter for type-checked projects, for running programs locally (without a browser)
+//!
+//! it follows the behaviour of the scratch target where the language leaves things open, so:
+//! - variables live in one global table keyed by their mangled identifiers (like scratch variables)
+//! - function arguments are assigned to their parameters one after the other
+//! - both sides of `&&` and `||` are always evaluated
+//! - `%` is a floored modulo and numbers are printed like javascript prints them
+//! - values are compared like scratch compares them (numerically if both look like numbers, otherwise as case-insensitive strings),
+//!   with lists & tuples compared by their rendered strings
+
+use std::{cmp::Ordering, collections::{BTreeMap, HashMap}, fmt::Display, io::{self, BufRead, Write}, ops::RangeInclusive, time::Instant};
+use ketchup::Span;
+use crate::scratch::emulator::{self, number_to_string};
+use super::{error::Diagnostic, typed::{block::TBlock, builtin::{dbg_label, FormatSegment, TBuiltinFnCall}, expr::TExpr, root::Project, stmt::TStmt}, Spanned};
+
+/// A runtime value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Nil,
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Renders the value like `as_str!` (strings are only quoted when `quoted`, like when they're within lists or tuples)
+    pub fn render(&self, quoted: bool) -> String {
+        match self {
+            Value::Number(num) => number_to_string(*num),
+            Value::String(string) if quoted => format!("\"{string}\""),
+            Value::String(string) => string.clone(),
+            Value::Bool(bool) => bool.to_string(),
+            Value::Nil => "()".to_string(),
+            Value::Tuple(values) => format!("({})", values.iter().map(|value| value.render(true)).collect::<Vec<_>>().join(", ")),
+            Value::List(values) => format!("[{}]", values.iter().map(|value| value.render(true)).collect::<Vec<_>>().join(", ")),
+        }
+    }
+
+    fn as_number(&self) -> f64 {
+        match self {
+            Value::Number(num) => *num,
+            _ => unreachable!("the type-checker only allows numbers here"),
+        }
+    }
+
+    fn as_bool(&self) -> bool {
+        match self {
+            Value::Bool(bool) => *bool,
+            _ => unreachable!("the type-checker only allows booleans here"),
+        }
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            Value::String(string) => string,
+            _ => unreachable!("the type-checker only allows strings here"),
+        }
+    }
+
+    fn into_list(self) -> Vec<Value> {
+        match self {
+            Value::List(list) => list,
+            _ => unreachable!("the type-checker only allows lists here"),
+        }
+    }
+
+    /// The value as the scratch target stores it (lists & tuples aren't values in scratch, so they're their rendered strings)
+    fn to_scratch(&self) -> emulator::Value {
+        match self {
+            Value::Number(num) => emulator::Value::Number(*num),
+            Value::String(string) => emulator::Value::String(string.clone()),
+            Value::Bool(bool) => emulator::Value::Bool(*bool),
+            value => emulator::Value::String(value.render(true)),
+        }
+    }
+
+    /// Orders two values of the same type like scratch does (for comparisons, sorting & searching lists)
+    fn compare(&self, other: &Value) -> Ordering {
+        self.to_scratch().compare(&other.to_scratch())
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// The reason a program stopped early
+#[derive(Debug)]
+pub enum RuntimeError {
+    /// The program panicked
+    Panic {
+        span: Span,
+        message: String,
+    },
+    /// Reading from stdin or writing to stdout failed
+    Io(io::Error),
+    /// The program was stopped early by a hook (like quitting the debugger)
+    Quit,
+}
+
+impl From<io::Error> for RuntimeError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl RuntimeError {
+    /// The diagnostic of a panic (io errors have no location in the source)
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        match self {
+            RuntimeError::Panic { span, message } => Some(Diagnostic::error(format!("the program panicked: {message}"), span.clone(), "panicked here")),
+            RuntimeError::Io(_) | RuntimeError::Quit => None,
+        }
+    }
+}
+
+/// Runs a project, reading input from `input` and printing to `output`
+///
+/// the `src` (named `src_id`) is the source code of the project (for the labels of `dbg`),
+/// and the `charset` is the set of unicode code-point ranges supported by the `char_code` and `from_char_code` builtins
+pub fn run(project: &Project, src_id: &str, src: &str, charset: &[RangeInclusive<u32>], input: impl BufRead, output: impl Write) -> Result<(), RuntimeError> {
+    Interpreter::new(&project.procedures, (src_id, src), charset, input, output).run(&project.main)
+}
+
+/// Runs one of the tests of a project (from `project.tests`) with a fresh set of variables
+pub fn run_test(project: &Project, test: &TBlock, src_id: &str, src: &str, charset: &[RangeInclusive<u32>], input: impl BufRead, output: impl Write) -> Result<(), RuntimeError> {
+    Interpreter::new(&project.procedures, (src_id, src), charset, input, output).run(test)
+}
+
+/// Runs a project like `run`, but calls the `hook` before every statement
+pub fn run_with_hook(project: &Project, src_id: &str, src: &str, charset: &[RangeInclusive<u32>], input: impl BufRead, output: impl Write, hook: &mut dyn Hook) -> Result<(), RuntimeError> {
+    let mut interpreter = Interpreter::new(&project.procedures, (src_id, src), charset, input, output);
+    interpreter.stack.push(Frame { procedure: None, scope: project.main.scope.clone(), span: 0..0 });
+    interpreter.hook = Some(hook);
+    interpreter.run(&project.main)
+}
+
+/// Something that's called before every statement of a running program (like the debugger)
+pub trait Hook {
+    /// Called before a statement runs (the statement is the `span` of the innermost frame of the `stack`), returning an error stops the program
+    ///
+    /// the `input` of the program is passed along so hooks can read from it too (stdin can only be locked once)
+    fn before_stmt(&mut self, stack: &[Frame], vars: &HashMap<String, Value>, input: &mut dyn BufRead) -> Result<(), RuntimeError>;
+}
+
+/// A procedure that's currently running (only tracked when there's a hook)
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// The identifier of the procedure (`None` for the main procedure)
+    pub procedure: Option<String>,
+    /// The prefix of the innermost scope that's running (like `$root$1`, see `VarTable::get_ident`)
+    pub scope: String,
+    /// The span of the statement that's running
+    pub span: Span,
+}
+
+impl Frame {
+    /// The variables that are visible to the running statement, by their names in the source
+    ///
+    /// variables are stored by their mangled identifiers (`{scope}/{name}`), so a variable is visible if it's scope is the running one or one of it's parents
+    /// (scopes are nested like `$root`, `$root$1`, `$root$1$2`), and shadowed variables are hidden by the innermost ones
+    pub fn visible_vars<'v>(&self, vars: &'v HashMap<String, Value>) -> BTreeMap<&'v str, &'v Value> {
+        let mut visible = BTreeMap::new();
+        let mut depths = HashMap::new();
+        for (ident, value) in vars.iter() {
+            let Some((scope, name)) = ident.rsplit_once('/') else { continue };
+            let in_scope = self.scope == scope || self.scope.strip_prefix(scope).is_some_and(|rest| rest.starts_with('$'));
+            if !in_scope || depths.get(name).is_some_and(|depth| *depth > scope.len()) { continue }
+
+            depths.insert(name, scope.len());
+            visible.insert(name, value);
+        }
+        visible
+    }
+}
+
+/// Procedures & variables that persist between the blocks run in it (like the entries of the repl)
+#[derive(Debug)]
+pub struct Session {
+    /// All the procedures defined so far
+    pub procedures: Vec<(String, TBlock)>,
+    /// All the variables by their mangled identifiers
+    pub vars: HashMap<String, Value>,
+    /// When the session started (for the `timer` builtin)
+    start: Instant,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            procedures: Vec::new(),
+            vars: HashMap::new(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Session {
+    /// Runs a block against the variables of the session and returns it's value
+    ///
+    /// variables set before a panic keep their new values
+    pub fn run(&mut self, block: &TBlock, src_id: &str, src: &str, charset: &[RangeInclusive<u32>], input: impl BufRead, output: impl Write) -> Result<Value, RuntimeError> {
+        let mut interpreter = Interpreter::new(&self.procedures, (src_id, src), charset, input, output);
+        interpreter.vars = std::mem::take(&mut self.vars);
+        interpreter.start = self.start;
+
+        let value = interpreter.block(block);
+        self.vars = interpreter.vars;
+        interpreter.output.flush()?;
+        value
+    }
+}
+
+/// The state of a running program
+struct Interpreter<'a, R: BufRead, W: Write> {
+    procedures: HashMap<&'a str, &'a TBlock>,
+    /// Called before every statement (if there is one)
+    hook: Option<&'a mut dyn Hook>,
+    /// The running procedures (only tracked when there's a hook)
+    stack: Vec<Frame>,
+    /// The name & contents of the source code (for the labels of `dbg`)
+    source: (&'a str, &'a str),
+    /// All the variables by their mangled identifiers
+    vars: HashMap<String, Value>,
+    charset: &'a [RangeInclusive<u32>],
+    input: R,
+    output: W,
+    /// When the program started (for the `timer` builtin)
+    start: Instant,
+}
+
+impl<'a, R: BufRead, W: Write> Interpreter<'a, R, W> {
+    fn new(procedures: &'a [(String, TBlock)], source: (&'a str, &'a str), charset: &'a [RangeInclusive<u32>], input: R, output: W) -> Self {
+        Self {
+            procedures: procedures.iter().map(|(ident, block)| (ident.as_str(), block)).collect(),
+            hook: None,
+            stack: Vec::new(),
+            source,
+            vars: HashMap::new(),
+            charset,
+            input,
+            output,
+            start: Instant::now(),
+        }
+    }
+
+    /// Runs a top-level block (like the main procedure) to completion
+    fn run(mut self, block: &TBlock) -> Result<(), RuntimeError> {
+        self.block(block)?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    /// Runs a block and returns it's value
+    fn block(&mut self, block: &TBlock) -> Result<Value, RuntimeError> {
+        // keep track of the innermost scope for the hook
+        let outer_scope = match (&self.hook, self.stack.last_mut()) {
+            (Some(_), Some(frame)) => Some(std::mem::replace(&mut frame.scope, block.scope.clone())),
+            _ => None,
+        };
+
+        for ((stmt, span), _) in block.stmts.iter() {
+            self.before_stmt(span)?;
+            self.stmt(stmt)?;
+        }
+
+        let value = match &block.tail {
+            Some(((TStmt::Expr(tail), span), _)) => {
+                self.before_stmt(span)?;
+                self.expr(tail)?
+            },
+            Some(((stmt, span), _)) => {
+                self.before_stmt(span)?;
+                self.stmt(stmt)?;
+                Value::Nil
+            },
+            None => Value::Nil,
+        };
+
+        if let (Some(scope), Some(frame)) = (outer_scope, self.stack.last_mut()) {
+            frame.scope = scope;
+        }
+        Ok(value)
+    }
+
+    /// Calls the hook (if there is one) before a statement runs
+    fn before_stmt(&mut self, span: &Span) -> Result<(), RuntimeError> {
+        let (Some(hook), Some(frame)) = (&mut self.hook, self.stack.last_mut()) else { return Ok(()) };
+        frame.span = span.clone();
+        hook.before_stmt(&self.stack, &self.vars, &mut self.input)
+    }
+
+    /// Runs a statement
+    fn stmt(&mut self, stmt: &TStmt) -> Result<(), RuntimeError> {
+        match stmt {
+            TStmt::Expr(expr) => { self.expr(expr)?; },
+            TStmt::VarDeclare { ident, value, .. } => {
+                let value = self.expr(&value.0.0)?;
+                self.set_var(ident, value);
+            },
+            TStmt::VarMutate { ident, value } => {
+                let value = self.expr(&value.0)?;
+                self.set_var(ident, value);
+            },
+            TStmt::If { cond, body, otherwise } => {
+                if self.expr(&cond.0.0)?.as_bool() {
+                    self.stmt(&body.0)?;
+                } else if let Some(otherwise) = otherwise {
+                    self.stmt(&otherwise.0)?;
+                }
+            },
+            TStmt::While { cond, body } => {
+                while self.expr(&cond.0.0)?.as_bool() {
+                    self.stmt(&body.0)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Sets a variable (without re-allocating it's identifier if it already exists)
+    fn set_var(&mut self, ident: &str, value: Value) {
+        match self.vars.get_mut(ident) {
+            Some(var) => *var = value,
+            None => { self.vars.insert(ident.to_string(), value); },
+        }
+    }
+
+    /// Evaluates an expr
+    fn expr(&mut self, expr: &TExpr) -> Result<Value, RuntimeError> {
+        use TExpr as E;
+
+        let number = |this: &mut Self, expr: &Spanned<TExpr>| this.expr(&expr.0).map(|value| value.as_number());
+
+        Ok(match expr {
+            // literals
+            E::Number(num) => Value::Number(*num),
+            E::String(string) => Value::String(string.clone()),
+            E::Bool(bool) => Value::Bool(*bool),
+            E::Nil => Value::Nil,
+
+            // maths
+            E::Add(lhs, rhs) => Value::Number(number(self, &lhs.0)? + number(self, &rhs.0)?),
+            E::Sub(lhs, rhs) => Value::Number(number(self, &lhs.0)? - number(self, &rhs.0)?),
+            E::Mul(lhs, rhs) => Value::Number(number(self, &lhs.0)? * number(self, &rhs.0)?),
+            E::Div(lhs, rhs) => Value::Number(number(self, &lhs.0)? / number(self, &rhs.0)?),
+            E::Mod(lhs, rhs) => {
+                let (lhs, rhs) = (number(self, &lhs.0)?, number(self, &rhs.0)?);
+                // the result takes the sign of the divisor (like scratch)
+                let result = lhs % rhs;
+                Value::Number(if result / rhs < 0.0 { result + rhs } else { result })
+            },
+            E::Neg(expr) => Value::Number(-number(self, &expr.0)?),
+            E::Pos(expr) => Value::Number(number(self, &expr.0)?),
+            E::Concat(lhs, rhs) => {
+                let lhs = self.expr(&lhs.0.0)?.into_string();
+                Value::String(lhs + &self.expr(&rhs.0.0)?.into_string())
+            },
+
+            // conditions
+            E::Not(expr) => Value::Bool(!self.expr(&expr.0.0)?.as_bool()),
+            E::And(lhs, rhs) => {
+                let lhs = self.expr(&lhs.0.0)?.as_bool();
+                Value::Bool(self.expr(&rhs.0.0)?.as_bool() && lhs)
+            },
+            E::Or(lhs, rhs) => {
+                let lhs = self.expr(&lhs.0.0)?.as_bool();
+                Value::Bool(self.expr(&rhs.0.0)?.as_bool() || lhs)
+            },
+            E::EE(lhs, rhs) => Value::Bool(self.compare(&lhs.0.0, &rhs.0.0)? == Ordering::Equal),
+            E::NE(lhs, rhs) => Value::Bool(self.compare(&lhs.0.0, &rhs.0.0)? != Ordering::Equal),
+            E::GT(lhs, rhs) => Value::Bool(self.compare(&lhs.0.0, &rhs.0.0)? == Ordering::Greater),
+            E::LT(lhs, rhs) => Value::Bool(self.compare(&lhs.0.0, &rhs.0.0)? == Ordering::Less),
+            E::GTE(lhs, rhs) => Value::Bool(self.compare(&lhs.0.0, &rhs.0.0)? != Ordering::Less),
+            E::LTE(lhs, rhs) => Value::Bool(self.compare(&lhs.0.0, &rhs.0.0)? != Ordering::Greater),
+
+            // compound values
+            E::Tuple(exprs) => Value::Tuple(exprs.iter().map(|expr| self.expr(&expr.0.0)).collect::<Result<_, _>>()?),
+            E::List(_, exprs) => Value::List(exprs.iter().map(|expr| self.expr(&expr.0)).collect::<Result<_, _>>()?),
+            E::Block(block) => self.block(block)?,
+
+            E::VarGet { ident, .. } => self.vars.get(ident).cloned().expect("variables are always declared before they're used"),
+
+            // function calls
+            E::Call(ident, args) => {
+                // pass the arguments through the parameter variables
+                for (param, arg) in args {
+                    let value = self.expr(&arg.0.0)?;
+                    self.set_var(param, value);
+                }
+
+                let procedure = self.procedures[ident.as_str()];
+                if self.hook.is_none() {
+                    return self.block(procedure);
+                }
+
+                self.stack.push(Frame { procedure: Some(ident.clone()), scope: procedure.scope.clone(), span: 0..0 });
+                let value = self.block(procedure)?;
+                self.stack.pop();
+                value
+            },
+
+            E::BuiltinFnCall(call) => self.builtin(call)?,
+        })
+    }
+
+    /// Evaluates both sides of a comparison and orders them
+    fn compare(&mut self, lhs: &TExpr, rhs: &TExpr) -> Result<Ordering, RuntimeError> {
+        let lhs = self.expr(lhs)?;
+        let rhs = self.expr(rhs)?;
+        Ok(lhs.compare(&rhs))
+    }
+
+    /// Evaluates a builtin-function call
+    fn builtin(&mut self, call: &TBuiltinFnCall) -> Result<Value, RuntimeError> {
+        use TBuiltinFnCall as B;
+
+        Ok(match call {
+            B::PrintLn(arg) => {
+                let line = match arg {
+                    Some(arg) => self.expr(&arg.0)?.into_string(),
+                    None => String::new(),
+                };
+                writeln!(self.output, "{line}")?;
+                Value::Nil
+            },
+            B::AsString(expr, _) => Value::String(self.expr(&expr.0)?.to_string()),
+            B::Input(prompt) => {
+                let prompt = self.expr(&prompt.0)?.into_string();
+                write!(self.output, "{prompt}")?;
+                self.output.flush()?;
+
+                // the end of the input is treated as an empty answer
+                let mut answer = String::new();
+                self.input.read_line(&mut answer)?;
+                let answer = answer.strip_suffix('\n').unwrap_or(&answer);
+                Value::String(answer.strip_suffix('\r').unwrap_or(answer).to_string())
+            },
+            B::Timer => Value::Number(self.start.elapsed().as_secs_f64()),
+            B::Panic(span, arg) => {
+                let message = match arg {
+                    Some(arg) => self.expr(&arg.0)?.into_string(),
+                    None => "explicit panic".to_string(),
+                };
+                return Err(RuntimeError::Panic { span: span.clone(), message });
+            },
+
+            B::Assert { span, cond, message } => {
+                if !self.expr(&cond.0)?.as_bool() {
+                    let message = match message {
+                        Some(message) => format!("assertion failed: {}", self.expr(&message.0)?.into_string()),
+                        None => format!("assertion failed: {}", &self.source.1[span.clone()]),
+                    };
+                    return Err(RuntimeError::Panic { span: span.clone(), message });
+                }
+                Value::Nil
+            },
+            B::AssertEq { span, lhs, rhs } => {
+                let lhs = self.expr(&lhs.0.0)?;
+                let rhs = self.expr(&rhs.0.0)?;
+                if lhs.compare(&rhs) != Ordering::Equal {
+                    let message = format!("assertion `left == right` failed\n  left: {}\n right: {}", lhs.render(true), rhs.render(true));
+                    return Err(RuntimeError::Panic { span: span.clone(), message });
+                }
+                Value::Nil
+            },
+            B::Dbg { span, expr } => {
+                let value = self.expr(&expr.0.0)?;
+                let (src_id, src) = self.source;
+                writeln!(self.output, "{} = {}", dbg_label(src_id, src, span), value.render(true))?;
+                value
+            },
+
+            // list builtins
+            B::ListLen(list) => Value::Number(self.expr(&list.0)?.into_list().len() as f64),
+            B::ListGet { span, list, idx } => {
+                let list = self.expr(&list.0)?.into_list();
+                let idx = self.expr(&idx.0)?.as_number();
+                let idx = index(span, idx, list.len(), false)?;
+                list[idx].clone()
+            },
+            B::ListPush { list, expr } => {
+                let value = self.expr(&expr.0)?;
+                self.list_mut(list, |list| {
+                    list.push(value);
+                    Ok(Value::Nil)
+                })?
+            },
+            B::ListInsert { span, list, idx, expr } => {
+                let value = self.expr(&expr.0)?;
+                let idx = self.expr(&idx.0)?.as_number();
+                self.list_mut(list, |list| {
+                    let idx = index(span, idx, list.len(), true)?;
+                    list.insert(idx, value);
+                    Ok(Value::Nil)
+                })?
+            },
+            B::ListRemove { span, list, idx } => {
+                let idx = self.expr(&idx.0)?.as_number();
+                self.list_mut(list, |list| {
+                    let idx = index(span, idx, list.len(), false)?;
+                    Ok(list.remove(idx))
+                })?
+            },
+            B::ListReplace { span, list, idx, expr } => {
+                let value = self.expr(&expr.0)?;
+                let idx = self.expr(&idx.0)?.as_number();
+                self.list_mut(list, |list| {
+                    let idx = index(span, idx, list.len(), false)?;
+                    list[idx] = value;
+                    Ok(Value::Nil)
+                })?
+            },
+            B::ListContains { list, expr } => {
+                let list = self.expr(&list.0)?.into_list();
+                let value = self.expr(&expr.0)?;
+                Value::Bool(list.iter().any(|item| item.compare(&value) == Ordering::Equal))
+            },
+            B::ListIndexOf { list, expr } => {
+                let list = self.expr(&list.0)?.into_list();
+                let value = self.expr(&expr.0)?;
+                // missing items have an index of `-1`
+                Value::Number(list.iter().position(|item| item.compare(&value) == Ordering::Equal).map(|idx| idx as f64).unwrap_or(-1.0))
+            },
+            B::ListClear(list) => self.list_mut(list, |list| {
+                list.clear();
+                Ok(Value::Nil)
+            })?,
+            B::ListReverse(list) => self.list_mut(list, |list| {
+                list.reverse();
+                Ok(Value::Nil)
+            })?,
+            B::ListSort(list) => self.list_mut(list, |list| {
+                list.sort_by(|lhs, rhs| lhs.compare(rhs));
+                Ok(Value::Nil)
+            })?,
+
+            // string builtins
+            B::StringLen(string) => Value::Number(self.expr(&string.0)?.into_string().chars().count() as f64),
+            B::StringGet { string, idx, .. } => {
+                let string = self.expr(&string.0)?.into_string();
+                let idx = self.expr(&idx.0)?.as_number();
+                // out of bounds characters are empty (like scratch)
+                Value::String(char_at(&string, idx).map(String::from).unwrap_or_default())
+            },
+            B::Format(segments) => {
+                let mut string = String::new();
+                for segment in segments {
+                    match segment {
+                        FormatSegment::Literal(literal) => string.push_str(literal),
+                        FormatSegment::Arg(expr, _) => string.push_str(&self.expr(&expr.0)?.to_string()),
+                    }
+                }
+                Value::String(string)
+            },
+
+            // character codes (characters outside of the charset have a code of `-1` and codes outside of it produce empty strings)
+            B::CharCode { string, idx } => {
+                let string = self.expr(&string.0)?.into_string();
+                let idx = self.expr(&idx.0)?.as_number();
+                let code = char_at(&string, idx)
+                    .map(|c| c as u32)
+                    .filter(|code| self.in_charset(*code));
+                Value::Number(code.map(|code| code as f64).unwrap_or(-1.0))
+            },
+            B::FromCharCode(code) => {
+                let code = self.expr(&code.0)?.as_number();
+                let c = Some(code)
+                    .filter(|code| code.fract() == 0.0 && *code >= 0.0 && *code <= u32::MAX as f64)
+                    .map(|code| code as u32)
+                    .filter(|code| self.in_charset(*code))
+                    .and_then(char::from_u32);
+                Value::String(c.map(String::from).unwrap_or_default())
+            },
+        })
+    }
+
+    /// Runs an in-place operation on a list (lists that aren't variables are temporary, so changes to them are lost)
+    fn list_mut(&mut self, list: &Spanned<TExpr>, f: impl FnOnce(&mut Vec<Value>) -> Result<Value, RuntimeError>) -> Result<Value, RuntimeError> {
+        if let TExpr::VarGet { ident, .. } = &list.0 {
+            match self.vars.get_mut(ident) {
+                Some(Value::List(list)) => f(list),
+                _ => unreachable!("variables are always declared before they're used"),
+            }
+        } else {
+            let mut list = self.expr(&list.0)?.into_list();
+            f(&mut list)
+        }
+    }
+
+    fn in_charset(&self, code: u32) -> bool {
+        self.charset.iter().any(|range| range.contains(&code))
+    }
+}
+
+/// Bounds-checks a list index (inserting may also happen at the end of the list)
+fn index(span: &Span, idx: f64, len: usize, inserting: bool) -> Result<usize, RuntimeError> {
+    if idx < 0.0 {
+        return Err(RuntimeError::Panic { span: span.clone(), message: format!("index cannot be negative: idx: {}", number_to_string(idx)) });
+    }
+
+    let max = if inserting { len } else { len.saturating_sub(1) };
+    if idx.is_nan() || (len == 0 && !inserting) || idx.floor() > max as f64 {
+        return Err(RuntimeError::Panic { span: span.clone(), message: format!("index out of bounds: len is {len} but the index is {}", number_to_string(idx)) });
+    }
+
+    Ok(idx.floor() as usize)
+}
+
+/// Gets the character at an index of a string
+fn char_at(string: &str, idx: f64) -> Option<char> {
+    if idx < 0.0 || idx.is_nan() {
+        return None;
+    }
+    string.chars().nth(idx.floor() as usize)
+}
@@ -0,0 +1,385 @@
+//! Lints, which warn about code that compiles but is likely a mistake
+//!
+//! (each lint can be allowed, warned or denied from the cli with `-A`/`-W`/`-D` or from the source with `#![allow(...)]`, `#![warn(...)]` or `#![deny(...)]`)
+
+use std::collections::HashSet;
+use clap::ValueEnum;
+use ketchup::Span;
+use crate::lang::{analysis::find_ident, cst::{self, CstTokenKind, TriviaKind}, edition::Edition, error::{warning::Warning, Diagnostic, Reportable, Severity}, parser::root::Roots, typed::{block::TBlock, builtin::{FormatSegment, TBuiltinFnCall}, expr::TExpr, root::Project, stmt::TStmt, types::Type}, Spanned};
+
+/// A lint that can be allowed, warned or denied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Lint {
+    /// Variables whose values are never read
+    #[value(name = "unused_variables")]
+    UnusedVariables,
+    /// Functions that are never called
+    #[value(name = "unused_functions")]
+    UnusedFunctions,
+    /// Variables declared `mut` that are never mutated
+    #[value(name = "unused_mut")]
+    UnusedMut,
+    /// Variables that shadow variables from an outer scope
+    #[value(name = "shadowing")]
+    Shadowing,
+    /// Code after a `panic!` that will never run
+    #[value(name = "unreachable_code")]
+    UnreachableCode,
+    /// Expression statements whose values are discarded
+    #[value(name = "unused_value")]
+    UnusedValue,
+}
+
+impl Lint {
+    /// All of the lints
+    pub const ALL: &'static [Lint] = &[Lint::UnusedVariables, Lint::UnusedFunctions, Lint::UnusedMut, Lint::Shadowing, Lint::UnreachableCode, Lint::UnusedValue];
+
+    /// The name of the lint (as used in the cli & source)
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnusedFunctions => "unused_functions",
+            Lint::UnusedMut => "unused_mut",
+            Lint::Shadowing => "shadowing",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::UnusedValue => "unused_value",
+        }
+    }
+
+    /// The level of the lint when it isn't set (shadowing is common & intentional, so it's opt-in)
+    pub fn default_level(self) -> Level {
+        match self {
+            Lint::Shadowing => Level::Allow,
+            _ => Level::Warn,
+        }
+    }
+
+    /// Finds a lint by it's name
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Self::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+}
+
+/// What happens when a lint is triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// Nothing is reported
+    Allow,
+    /// A warning is reported
+    Warn,
+    /// An error is reported
+    Deny,
+}
+
+/// The levels of lints (later levels take priority over earlier ones)
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels(pub Vec<(Lint, Level)>);
+
+impl LintLevels {
+    /// Sets the level of a lint
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        self.0.push((lint, level));
+    }
+
+    /// Gets the level of a lint
+    pub fn level(&self, lint: Lint) -> Level {
+        self.0.iter()
+            .rev()
+            .find(|(other, _)| *other == lint)
+            .map(|(_, level)| *level)
+            .unwrap_or(lint.default_level())
+    }
+}
+
+/// Applies the lint directives (like `#![allow(unused_variables, shadowing)]`) in the `#` comments of a source file, returning any unknown lints
+pub fn apply_directives(src: &str, edition: Edition, levels: &mut LintLevels) -> Vec<Warning> {
+    let mut unknown = Vec::new();
+
+    for token in cst::lex(src, edition) {
+        if token.kind != CstTokenKind::Trivia(TriviaKind::LineComment) { continue }
+
+        // directives look like `#![level(lint, ...)]`
+        let Some(directive) = src[token.span.clone()].strip_prefix("#![").and_then(|directive| directive.trim_end().strip_suffix(")]"))
+        else { continue };
+        let Some((level, lints)) = directive.split_once('(') else { continue };
+        let level = match level.trim() {
+            "allow" => Level::Allow,
+            "warn" => Level::Warn,
+            "deny" => Level::Deny,
+            _ => continue,
+        };
+
+        for name in lints.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match Lint::from_name(name) {
+                Some(lint) => levels.set(lint, level),
+                None => unknown.push(Warning::UnknownLint { name: name.to_string(), span: token.span.clone() }),
+            }
+        }
+    }
+
+    unknown
+}
+
+/// Lints a type-checked project, returning the diagnostics of all the lints that aren't allowed (denied lints are errors)
+pub fn lint(src: &str, edition: Edition, roots: &Roots, project: &Project, levels: &LintLevels) -> Vec<Diagnostic> {
+    // lint directives in the source take priority over the cli
+    let mut levels = levels.clone();
+    let mut diagnostics = apply_directives(src, edition, &mut levels).iter().map(Reportable::diagnostic).collect::<Vec<_>>();
+
+    let mut linter = Linter { src, defs: Vec::new(), calls: HashSet::new(), func: None, warnings: Vec::new() };
+    linter.block(&project.main);
+    for (ident, block) in project.procedures.iter() {
+        // parameters are variables too
+        if let Some((func, _)) = roots.funcs.iter().find(|(func, _)| &func.ident == ident) {
+            for ((param, _), span) in func.params.iter() {
+                linter.declare(format!("$func${ident}/{param}"), find_ident(src, span, param), false);
+            }
+        }
+
+        linter.func = Some(ident.clone());
+        linter.block(block);
+    }
+    linter.func = None;
+    for (_, block) in project.tests.iter() {
+        linter.block(block);
+    }
+    linter.finish(roots);
+
+    for warning in linter.warnings {
+        let Some(lint) = warning.lint() else { continue };
+        let name = lint.name();
+
+        let diagnostic = warning.diagnostic();
+        diagnostics.push(match levels.level(lint) {
+            Level::Allow => continue,
+            Level::Warn => diagnostic.with_note(format!("`{name}` is a lint, silence it with `#![allow({name})]` or `--allow {name}`")),
+            Level::Deny => Diagnostic { severity: Severity::Error, ..diagnostic }
+                .with_note(format!("`{name}` is a lint that's denied (with `#![deny({name})]` or `--deny {name}`)")),
+        });
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.primary_span().start);
+    diagnostics
+}
+
+/// A variable declaration
+struct VarDef {
+    /// The unique identifier of the variable
+    ident: String,
+    span: Span,
+    mutable: bool,
+    used: bool,
+    mutated: bool,
+}
+
+/// Walks the typed tree, gathering lint warnings
+struct Linter<'a> {
+    src: &'a str,
+    /// The variable declarations in order of appearance
+    defs: Vec<VarDef>,
+    /// The functions that are called (outside of themselves)
+    calls: HashSet<String>,
+    /// The function that's currently being walked
+    func: Option<String>,
+    warnings: Vec<Warning>,
+}
+
+/// Splits a unique variable identifier into it's scope and name
+fn split_ident(ident: &str) -> (&str, &str) {
+    ident.rsplit_once('/').unwrap_or(("", ident))
+}
+
+impl Linter<'_> {
+    /// Declares a new variable, checking if it shadows a variable from an outer scope
+    fn declare(&mut self, ident: String, span: Span, mutable: bool) {
+        let (scope, name) = split_ident(&ident);
+        let shadowed = self.defs.iter().rev().find(|def| {
+            let (def_scope, def_name) = split_ident(&def.ident);
+            def_name == name && scope.len() > def_scope.len() && scope.starts_with(def_scope) && scope[def_scope.len()..].starts_with('$')
+        });
+
+        if let Some(shadowed) = shadowed {
+            if !name.starts_with('_') {
+                self.warnings.push(Warning::Shadowing { ident: name.to_string(), span: span.clone(), shadowed_span: shadowed.span.clone() });
+            }
+        }
+
+        self.defs.push(VarDef { ident, span, mutable, used: false, mutated: false });
+    }
+
+    /// Reports the unused variables & functions once the entire project has been walked
+    fn finish(&mut self, roots: &Roots) {
+        for def in self.defs.iter() {
+            let name = split_ident(&def.ident).1;
+            if name.starts_with('_') { continue }
+
+            if !def.used {
+                self.warnings.push(Warning::UnusedVariable { ident: name.to_string(), span: def.span.clone() });
+            }
+            if def.mutable && !def.mutated {
+                self.warnings.push(Warning::UnusedMut { ident: name.to_string(), span: def.span.clone() });
+            }
+        }
+
+        for (func, span) in roots.funcs.iter() {
+            if !func.test && !func.ident.starts_with('_') && !self.calls.contains(&func.ident) {
+                self.warnings.push(Warning::UnusedFunction { ident: func.ident.clone(), span: find_ident(self.src, span, &func.ident) });
+            }
+        }
+    }
+
+    /// Walks a block, returning if it always panics
+    fn block(&mut self, block: &TBlock) -> bool {
+        let stmts = block.stmts.iter().chain(block.tail.iter()).collect::<Vec<_>>();
+        let mut diverges = false;
+
+        for (i, ((stmt, span), stmt_type)) in stmts.iter().enumerate() {
+            // values of expression statements (besides the tail) are discarded
+            if let TStmt::Expr(expr) = stmt {
+                let is_tail = i == block.stmts.len();
+                if !is_tail && *stmt_type != Type::Nil && !matches!(expr, TExpr::Call(..) | TExpr::BuiltinFnCall(_) | TExpr::Block(_)) {
+                    self.warnings.push(Warning::UnusedValue { span: span.clone(), value_type: stmt_type.clone() });
+                }
+            }
+
+            // everything after a statement that always panics is unreachable (but is still walked for the other lints)
+            if self.stmt(stmt, Some(span)) && !diverges {
+                diverges = true;
+                if let Some(((_, next_span), _)) = stmts.get(i + 1) {
+                    let end = stmts[stmts.len() - 1].0.1.end;
+                    self.warnings.push(Warning::UnreachableCode { span: next_span.start..end, panic_span: span.clone() });
+                }
+            }
+        }
+
+        diverges
+    }
+
+    /// Walks a statement, returning if it always panics (the span is only known for statements directly within blocks)
+    fn stmt(&mut self, stmt: &TStmt, span: Option<&Span>) -> bool {
+        match stmt {
+            TStmt::Expr(expr) => self.expr(expr),
+
+            TStmt::VarDeclare { ident, mutable, value: ((value, value_span), _) } => {
+                let diverges = self.expr(value);
+
+                // the identifier is between the start of the statement and the value
+                let start = span.map(|span| span.start).unwrap_or(value_span.start);
+                let ident_span = find_ident(self.src, &(start..value_span.start), split_ident(ident).1);
+                self.declare(ident.clone(), ident_span, *mutable);
+
+                diverges
+            },
+
+            TStmt::VarMutate { ident, value: (value, _) } => {
+                let diverges = match value {
+                    // the variable get generated by operations like `+=` isn't a read of the variable
+                    TExpr::Add(lhs, rhs) | TExpr::Sub(lhs, rhs) | TExpr::Mul(lhs, rhs) | TExpr::Div(lhs, rhs) | TExpr::Mod(lhs, rhs)
+                        if matches!(&lhs.0.0, TExpr::VarGet { ident: var, .. } if var == ident) && lhs.0.1 == rhs.0.1 => self.expr(&rhs.0.0),
+                    _ => self.expr(value),
+                };
+
+                if let Some(def) = self.defs.iter_mut().rev().find(|def| &def.ident == ident) {
+                    def.mutated = true;
+                }
+                diverges
+            },
+
+            TStmt::If { cond, body, otherwise } => {
+                let cond = self.expr(&cond.0.0);
+                let body = self.stmt(&body.0, None);
+                let otherwise = otherwise.as_ref().map(|otherwise| self.stmt(&otherwise.0, None)).unwrap_or(false);
+                cond || (body && otherwise)
+            },
+
+            TStmt::While { cond, body } => {
+                let cond = self.expr(&cond.0.0);
+                self.stmt(&body.0, None);
+                cond
+            },
+        }
+    }
+
+    /// Walks an expression, returning if it always panics
+    fn expr(&mut self, expr: &TExpr) -> bool {
+        let exprs: Vec<&TExpr> = match expr {
+            TExpr::Number(_) | TExpr::String(_) | TExpr::Bool(_) | TExpr::Nil => vec![],
+
+            TExpr::Add(lhs, rhs) | TExpr::Sub(lhs, rhs) | TExpr::Mul(lhs, rhs) | TExpr::Div(lhs, rhs) | TExpr::Mod(lhs, rhs)
+            | TExpr::Concat(lhs, rhs) | TExpr::Or(lhs, rhs) | TExpr::And(lhs, rhs)
+            | TExpr::EE(lhs, rhs) | TExpr::NE(lhs, rhs) | TExpr::GT(lhs, rhs) | TExpr::LT(lhs, rhs) | TExpr::GTE(lhs, rhs) | TExpr::LTE(lhs, rhs) => vec![&lhs.0.0, &rhs.0.0],
+
+            TExpr::Neg(expr) | TExpr::Pos(expr) | TExpr::Not(expr) => vec![&expr.0.0],
+            TExpr::Tuple(exprs) => exprs.iter().map(|expr| &expr.0.0).collect(),
+            TExpr::List(_, exprs) => exprs.iter().map(|(expr, _)| expr).collect(),
+            TExpr::Block(block) => return self.block(block),
+
+            TExpr::Call(ident, args) => {
+                if self.func.as_ref() != Some(ident) {
+                    self.calls.insert(ident.clone());
+                }
+                args.iter().map(|(_, arg)| &arg.0.0).collect()
+            },
+
+            TExpr::VarGet { ident, .. } => {
+                if let Some(def) = self.defs.iter_mut().rev().find(|def| &def.ident == ident) {
+                    def.used = true;
+                }
+                vec![]
+            },
+
+            TExpr::BuiltinFnCall(call) => return self.builtin(call),
+        };
+
+        // an expression panics if any of it's parts do (evaluation is always eager)
+        // (every part is still walked, unlike with `any`, so all of their variables are marked as used)
+        let mut diverges = false;
+        for expr in exprs {
+            diverges |= self.expr(expr);
+        }
+        diverges
+    }
+
+    /// Walks a builtin function call, returning if it always panics
+    fn builtin(&mut self, call: &TBuiltinFnCall) -> bool {
+        use TBuiltinFnCall as B;
+
+        // builtins that modify lists in-place count as mutations (even though they don't require `mut`)
+        if let B::ListPush { list, .. } | B::ListInsert { list, .. } | B::ListRemove { list, .. } | B::ListReplace { list, .. }
+        | B::ListClear(list) | B::ListReverse(list) | B::ListSort(list) = call {
+            if let TExpr::VarGet { ident, .. } = &list.0 {
+                if let Some(def) = self.defs.iter_mut().rev().find(|def| &def.ident == ident) {
+                    def.mutated = true;
+                }
+            }
+        }
+
+        let exprs: Vec<&Spanned<TExpr>> = match call {
+            B::Panic(_, expr) => {
+                if let Some((expr, _)) = expr { self.expr(expr); }
+                return true;
+            },
+
+            B::Timer | B::PrintLn(None) => vec![],
+            B::PrintLn(Some(expr)) | B::Input(expr) | B::ListLen(expr) | B::StringLen(expr) | B::FromCharCode(expr)
+            | B::ListClear(expr) | B::ListReverse(expr) | B::ListSort(expr) | B::AsString(expr, _) => vec![expr],
+            B::ListGet { list, idx, .. } | B::ListRemove { list, idx, .. } => vec![list, idx],
+            B::ListPush { list, expr } | B::ListContains { list, expr } | B::ListIndexOf { list, expr } => vec![list, expr],
+            B::ListInsert { list, idx, expr, .. } | B::ListReplace { list, idx, expr, .. } => vec![list, idx, expr],
+            B::StringGet { string, idx, .. } | B::CharCode { string, idx } => vec![string, idx],
+            B::Assert { cond, message, .. } => std::iter::once(cond).chain(message).collect(),
+            B::AssertEq { lhs, rhs, .. } => vec![&lhs.0, &rhs.0],
+            B::Dbg { expr, .. } => vec![&expr.0],
+            B::Format(segments) => segments.iter().filter_map(|segment| match segment {
+                FormatSegment::Arg(expr, _) => Some(expr),
+                _ => None,
+            }).collect(),
+        };
+
+        let mut diverges = false;
+        for (expr, _) in exprs {
+            diverges |= self.expr(expr);
+        }
+        diverges
+    }
+}
@@ -11,6 +11,10 @@ pub struct FuncDef {
     pub params: Vec<Spanned<(String, Type)>>,
     pub retrn_type: Spanned<Type>,
     pub body: Spanned<Block>,
+    /// The doc comments (one per line) attached to the function
+    pub docs: Vec<String>,
+    /// If the function is a test (`test fn`), which is only ever run by `scrapile test`
+    pub test: bool,
 }
 
 /// Parses a function definition (given that the `fn` token has already been consumed)
@@ -58,6 +62,8 @@ pub fn parse_fn(tokens: &mut SpannedIter<'_, Token>) -> Result<Spanned<FuncDef>,
             params: params.unwrap_or_default(),
             retrn_type,
             body,
+            docs: Vec::new(), // attached later by the root parser
+            test: false, // also set later by the root parser
         },
         start_span.start..tokens.span().end
     ))
@@ -8,34 +8,69 @@ use super::{block::Block, function::{self, FuncDef}};
 #[derive(Debug, Default)]
 pub struct Roots {
     pub main: Vec<Spanned<Block>>,
+    /// The doc comments (one per line) of the main procedure
+    pub main_docs: Vec<String>,
     pub funcs: Vec<Spanned<FuncDef>>,
 }
 
 /// Parses the roots of the project (stuff that will never be placed in a within any block or scope)
 pub fn parse_root(tokens: &mut SpannedIter<'_, Token>) -> Result<Roots, Vec<KError<Error>>> {
     let mut roots = Roots::default();
+    let mut docs = Vec::new();
+    let mut docs_span = None;
     
     // parse every root in the project
     while let Some((token, span)) = tokens.next() {
         match token {
             Err(err) => return Err(vec![KError::Other(span, err)]),
 
+            // collect doc comments for the next root
+            Ok(Token::DocComment(doc)) => {
+                docs.push(doc);
+                docs_span.get_or_insert(span);
+            },
+
             Ok(Token::Main) => {
                 // parse and push the main root
                 let (main, span) = parse_main(tokens)?;
                 roots.main.push((main, span));
+                roots.main_docs.append(&mut docs);
+                docs_span = None;
             },
 
             Ok(Token::Func) => {
                 // parse and push the function
-                let (func, span) = function::parse_fn(tokens)?;
+                let (mut func, span) = function::parse_fn(tokens)?;
+                func.docs = std::mem::take(&mut docs);
+                docs_span = None;
                 roots.funcs.push((func, span));
             },
             
+            // `test` is only a keyword before `fn` at the root, so it's still a valid identifier everywhere else
+            Ok(Token::Ident(ident)) if ident == "test" => {
+                match tokens.next() {
+                    Some((Ok(Token::Func), _)) => (),
+                    Some((Err(err), span)) => return Err(vec![KError::Other(span, err)]),
+                    _ => return Err(vec![KError::Other(tokens.span(), Error::ExpectedRoot)]),
+                }
+
+                // parse and push the test function
+                let (mut func, fn_span) = function::parse_fn(tokens)?;
+                func.docs = std::mem::take(&mut docs);
+                func.test = true;
+                docs_span = None;
+                roots.funcs.push((func, span.start..fn_span.end));
+            },
+
             _ => return Err(vec![KError::Other(span, Error::ExpectedRoot)]),
         }
     }
 
+    // doc comments at the end of the file
+    if let Some(span) = docs_span {
+        return Err(vec![KError::Other(span, Error::DanglingDocComment)]);
+    }
+
     Ok(roots)
 }
 
@@ -78,6 +78,7 @@ pub fn parse_stmt(first_tok: Option<Spanned<Result<Token, Error>>>, tokens: &mut
         Token::Mut => parse_var_mutate(tokens),
         Token::If => parse_if(tokens),
         Token::While => parse_while(tokens),
+        Token::DocComment(_) => Err(vec![KError::Other(start_span, Error::DanglingDocComment)]),
         
         // assume that the statement is a expr
         _ => parse_expr(Some((Ok(first_tok), start_span.clone())), tokens).map(|(expr, next_tok)| ((Stmt::Expr(expr.clone()), start_span.start..expr.span.end), next_tok.map(|(tok, span)| (Ok(tok), span)))),
@@ -0,0 +1,190 @@
+//! A read-eval-print loop for experimenting with the language, entries are type-checked & run one at a time against the same variables & functions
+//!
+//! every entry is either:
+//! - function definitions (`fn`s, which can call each other & any previously defined ones)
+//! - statements (like the inside of a block), where the value of the tail is printed with it's type
+
+use std::{collections::HashMap, io::{BufRead, Write}, ops::RangeInclusive};
+use ketchup::{error::KError, Span};
+use logos::{Logos, SpannedIter};
+use crate::compile::Diagnostics;
+use super::{edition::Edition, error::{parser, typed::Error, Reportable}, interpreter::{RuntimeError, Session, Value}, parser::{block::Block, root, stmt::{parse_stmt, Stmt}}, token::Token, typed::{block::TBlock, root::{signature, wrap_func}, stmt::{self, TStmt}, symbol_table::{FuncTable, TypeTable, VarTable}, types::Type}, Spanned};
+
+/// The reason an entry wasn't (fully) run
+#[derive(Debug)]
+pub enum ReplError {
+    /// The entry didn't parse or type-check (so nothing was run)
+    Compile(Diagnostics),
+    /// The entry was run but stopped early
+    Runtime(RuntimeError),
+}
+
+/// The state of the repl
+#[derive(Debug)]
+pub struct Repl {
+    edition: Edition,
+    charset: Vec<RangeInclusive<u32>>,
+
+    /// Every entry so far (one after the other), so spans from previous entries can still be reported
+    history: String,
+
+    type_table: TypeTable,
+    func_table: FuncTable,
+    var_table: VarTable,
+    session: Session,
+}
+
+impl Repl {
+    /// Creates a new repl without any variables or functions
+    pub fn new(edition: Edition, charset: Vec<RangeInclusive<u32>>) -> Self {
+        Self {
+            edition,
+            charset,
+            history: String::new(),
+            type_table: TypeTable(HashMap::new()),
+            func_table: FuncTable(HashMap::new()),
+            var_table: VarTable::new("$repl".to_string()),
+            session: Session::default(),
+        }
+    }
+
+    /// The source code of every entry so far (which the spans of errors point into)
+    pub fn source(&self) -> &str {
+        &self.history
+    }
+
+    /// If an entry still has unclosed braces, brackets or parentheses (so it continues onto the next line)
+    pub fn is_unfinished(&self, entry: &str) -> bool {
+        let depth = Token::lexer_with_extras(entry, self.edition.into())
+            .fold(0isize, |depth, token| match token {
+                Ok(Token::LBrace | Token::LBracket | Token::LParen) => depth + 1,
+                Ok(Token::RBrace | Token::RBracket | Token::RParen) => depth - 1,
+                _ => depth,
+            });
+        depth > 0
+    }
+
+    /// Type-checks & runs an entry, returning the value of it's tail (with it's type) if it has one
+    ///
+    /// variables & functions are only kept if the entry runs successfully
+    pub fn eval(&mut self, entry: &str, input: impl BufRead, output: impl Write) -> Result<Option<(Value, Type)>, ReplError> {
+        // entries are kept on seperate lines of the history
+        if !self.history.is_empty() {
+            self.history.push('\n');
+        }
+        let start = self.history.len();
+        self.history.push_str(entry);
+
+        // decide what kind of entry it is from it's first tokens
+        let mut tokens = self.tokens(start);
+        match (tokens.next(), tokens.next()) {
+            (Some((Ok(Token::Func | Token::Main | Token::DocComment(_)), _)), _) => (),
+            (Some((Ok(Token::Ident(ident)), _)), Some((Ok(Token::Func), _))) if ident == "test" => (),
+            _ => return self.eval_stmts(start, input, output),
+        }
+
+        self.define_funcs(start).map(|_| None)
+    }
+
+    /// The tokens of the entry starting at `start` in the history (so their spans line up with the history)
+    fn tokens(&self, start: usize) -> SpannedIter<'_, Token> {
+        let mut lexer = Token::lexer_with_extras(&self.history, self.edition.into());
+        lexer.bump(start);
+        lexer.spanned()
+    }
+
+    /// Type-checks & defines the functions of an entry
+    fn define_funcs(&mut self, start: usize) -> Result<(), ReplError> {
+        let roots = root::parse_root(&mut self.tokens(start)).map_err(parse_errors)?;
+
+        // only functions can be defined (a main procedure would never run, and neither would tests)
+        let invalid = roots.main.iter().map(|(_, span)| span)
+            .chain(roots.funcs.iter().filter(|(func, _)| func.test).map(|(_, span)| span))
+            .next();
+        if let Some(span) = invalid {
+            return Err(typed_error(Error::ReplRoot { span: span.clone() }));
+        }
+
+        // gather the signatures first, so the functions can call each other
+        let mut func_table = self.func_table.clone();
+        for func in roots.funcs.iter() {
+            // functions can't be re-defined, as the previous ones may be called by others with the old signature
+            if let Some((_, first_span)) = func_table.0.get(&func.0.ident) {
+                return Err(typed_error(Error::MultipleFunc {
+                    first_span: first_span.clone(),
+                    additional_span: func.1.clone(),
+                }));
+            }
+            func_table.0.insert(func.0.ident.clone(), signature(func));
+        }
+
+        let procedures = roots.funcs.iter()
+            .map(|(func, _)| wrap_func(func, &self.type_table, &func_table).map(|block| (func.ident.clone(), block)).map_err(typed_error))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.func_table = func_table;
+        self.session.procedures.extend(procedures);
+        Ok(())
+    }
+
+    /// Type-checks & runs the statements of an entry
+    fn eval_stmts(&mut self, start: usize, input: impl BufRead, output: impl Write) -> Result<Option<(Value, Type)>, ReplError> {
+        let block = parse_stmts(&mut self.tokens(start), start..self.history.len()).map_err(parse_errors)?;
+
+        // wrap the statements against a copy of the variables, so a failing entry doesn't declare anything
+        let mut var_table = self.var_table.clone();
+        let mut wrap = |(stmt, span): Spanned<Stmt>| stmt::wrap_stmt((stmt, span.clone()), &self.type_table, &self.func_table, &mut var_table)
+            .map(|(stmt, stmt_type)| ((stmt, span), stmt_type))
+            .map_err(typed_error);
+        let stmts = block.stmts.into_iter().map(&mut wrap).collect::<Result<Vec<_>, _>>()?;
+        let tail = block.tail.map(wrap).transpose()?;
+
+        // only expressions have values worth printing
+        let tail_type = match &tail {
+            Some(((TStmt::Expr(_), _), tail_type)) if *tail_type != Type::Nil => Some(tail_type.clone()),
+            _ => None,
+        };
+
+        // a panic also rolls back the variables, so they always match their types
+        let vars = self.session.vars.clone();
+        let value = match self.session.run(&TBlock { stmts, tail, scope: self.var_table.prefix().to_string() }, "<repl>", &self.history, &self.charset, input, output) {
+            Ok(value) => value,
+            Err(err) => {
+                self.session.vars = vars;
+                return Err(ReplError::Runtime(err));
+            },
+        };
+
+        self.var_table = var_table;
+        Ok(tail_type.map(|tail_type| (value, tail_type)))
+    }
+}
+
+/// Parses statements like the inside of a block, except that they end at the end of the entry (`entry_span`) instead of a `}`
+fn parse_stmts(tokens: &mut SpannedIter<'_, Token>, entry_span: Span) -> Result<Block, Vec<KError<parser::Error>>> {
+    let mut stmts = Vec::new();
+    let mut next_tok = tokens.next();
+
+    // an empty entry (or one ending with a `;`) has no tail
+    while next_tok.is_some() {
+        let (stmt, local_next_tok) = parse_stmt(next_tok, tokens)?;
+        stmts.push(stmt);
+
+        match local_next_tok {
+            None => return Ok(Block { tail: stmts.pop(), stmts }),
+            Some((Ok(Token::SemiColon), _)) => next_tok = tokens.next(),
+            Some((Err(err), span)) => return Err(vec![KError::Other(span, err)]),
+            Some((Ok(_), span)) => return Err(vec![KError::Other(span, parser::Error::ExpectedSemiOrRBrace { ctx_span: entry_span })]),
+        }
+    }
+
+    Ok(Block { stmts, tail: None })
+}
+
+fn parse_errors(errors: Vec<KError<parser::Error>>) -> ReplError {
+    ReplError::Compile(Diagnostics(errors.iter().map(Reportable::diagnostic).collect()))
+}
+
+fn typed_error(error: Error) -> ReplError {
+    ReplError::Compile(Diagnostics(vec![error.diagnostic()]))
+}
@@ -1,7 +1,12 @@
-use crate::{lang::typed::{expr::TExpr, root::Project, stmt::TStmt, types::Type}, scratch::{Assembly, Condition, Expr, Procedure, Statement}};
+use std::{collections::{BTreeSet, HashSet}, ops::RangeInclusive};
+use ketchup::Span;
+use crate::{lang::{typed::{builtin::{dbg_label, FormatSegment, TBuiltinFnCall}, expr::TExpr, root::Project, stmt::TStmt, types::{Type, Typed}}, Spanned}, scratch::{Assembly, Condition, Expr, Procedure, Statement}};
 
 /// Translates a project into scratch assembly
-pub fn translate(project: Project) -> Assembly {
+///
+/// the `src` (named `src_id`) is the source code of the project (for the labels of `dbg`),
+/// and the `charset` is the set of unicode code-point ranges supported by the `char_code` and `from_char_code` builtins
+pub fn translate(project: Project, src_id: &str, src: &str, charset: &[RangeInclusive<u32>]) -> Assembly {
     let mut stmts = vec![Statement::ClearList { ident: "console".to_string() }]; // first statement is to clear the console
     let mut tmp_binds = 0; // temporary binding idx
 
@@ -77,22 +82,260 @@ pub fn translate(project: Project) -> Assembly {
         procedures.push(Procedure { ident, body: stmts });
     }
 
+    // only emit the character lookup tables if they're actually used
+    let mut lists = Vec::new();
+    let mut backdrops = Vec::new();
+    let mut called = HashSet::new();
+    called_procedures(&stmts, &mut called);
+    for procedure in procedures.iter() {
+        called_procedures(&procedure.body, &mut called);
+    }
+    if called.contains(CHAR_CODE) || called.contains(FROM_CHAR_CODE) {
+        let tables = tcharset(charset);
+        procedures.extend(tables.procedures);
+        lists.extend(tables.lists);
+        backdrops.extend(tables.backdrops);
+    }
+
+    // same with the list procedures
+    if called.contains(LIST_REVERSE) {
+        procedures.push(list_reverse_procedure());
+    }
+    if called.contains(LIST_SORT) {
+        procedures.push(list_sort_procedure());
+    }
+
+    // and a procedure for each `dbg` call & message-less `assert` (sorted so the output is deterministic)
+    for span in called_spans(&called, DBG_PREFIX) {
+        procedures.push(dbg_procedure(src_id, src, span));
+    }
+    for span in called_spans(&called, ASSERT_PREFIX) {
+        procedures.push(assert_procedure(src, span));
+    }
+
     Assembly {
         stmts,
         variables: Vec::new(),
-        lists: Vec::new(),
+        lists,
         procedures,
+        backdrops,
     }
 }
 
 const NIL: &str = "<nil>";
 const PANIC_NAME: &str = "$panic$msg";
 
+// # Character Lookup Tables
+// ---
+// scratch has no way to convert between characters and their codes, and
+// also compares strings case-insensitively, so:
+// - `$charset` & `$charset$codes` hold every character and it's code (in the same order)
+// - `$charset$search` is `$charset` with all the upper-case characters masked out (so they can't shadow their lower-case counterparts)
+// - upper-case characters are instead given their own backdrop (backdrop names are case-sensitive) and their codes are in `$charset$cased`
+const CHAR_CODE: &str = "$char_code";
+const CHAR_CODE_ARG: &str = "$char_code$char";
+const CHAR_CODE_RETURN: &str = "$char_code$return";
+const FROM_CHAR_CODE: &str = "$from_char_code";
+const FROM_CHAR_CODE_ARG: &str = "$from_char_code$code";
+const FROM_CHAR_CODE_RETURN: &str = "$from_char_code$return";
+const CHARSET: &str = "$charset";
+const CHARSET_CODES: &str = "$charset$codes";
+const CHARSET_SEARCH: &str = "$charset$search";
+const CHARSET_CASED: &str = "$charset$cased";
+const CHARSET_MASK: &str = "$cased";
+const DEFAULT_BACKDROP: &str = "backdrop1";
+
+// # List Procedures
+// ---
+// scratch procedures can't take lists as arguments, so the list procedures
+// operate on their own list which the list is copied to and from
+const LIST_REVERSE: &str = "$list_reverse";
+const LIST_REVERSE_LIST: &str = "$list_reverse$list";
+const LIST_REVERSE_I: &str = "$list_reverse$i";
+const LIST_REVERSE_J: &str = "$list_reverse$j";
+const LIST_REVERSE_TMP: &str = "$list_reverse$tmp";
+const LIST_SORT: &str = "$list_sort";
+const LIST_SORT_LIST: &str = "$list_sort$list";
+const LIST_SORT_I: &str = "$list_sort$i";
+const LIST_SORT_J: &str = "$list_sort$j";
+const LIST_SORT_KEY: &str = "$list_sort$key";
+
+// # Source Procedures
+// ---
+// the translation of exprs has no access to the source code, so each `dbg`
+// call sets the rendered value & calls a procedure named after it's span,
+// which prints the label (generated once the source is known in `translate`),
+// and failed `assert`s without messages call a procedure that panics with the condition
+const DBG_PREFIX: &str = "$dbg$";
+const DBG_ARG: &str = "$dbg$value";
+const ASSERT_PREFIX: &str = "$assert$";
+
+/// Collects the identifiers of all the procedures called within some statements
+fn called_procedures(stmts: &[Statement], called: &mut HashSet<String>) {
+    for stmt in stmts {
+        match stmt {
+            Statement::CallProcedure { ident } => { called.insert(ident.clone()); },
+            Statement::If { body, .. } | Statement::RepeatUntil { body, .. } => called_procedures(body, called),
+            Statement::IfElse { body, otherwise, .. } => {
+                called_procedures(body, called);
+                called_procedures(otherwise, called);
+            },
+            _ => (),
+        }
+    }
+}
+
+/// The identifier of a procedure named after a span of the source
+fn span_ident(prefix: &str, span: &Span) -> String {
+    format!("{prefix}{}..{}", span.start, span.end)
+}
+
+/// The spans of the called procedures named after a span of the source (in order)
+fn called_spans(called: &HashSet<String>, prefix: &str) -> Vec<Span> {
+    called.iter()
+        .filter_map(|ident| ident.strip_prefix(prefix)?.split_once(".."))
+        .filter_map(|(start, end)| Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|(start, end)| start..end)
+        .collect()
+}
+
+/// Generates the procedure that prints the label & value of a `dbg` call
+fn dbg_procedure(src_id: &str, src: &str, span: Span) -> Procedure {
+    Procedure {
+        ident: span_ident(DBG_PREFIX, &span),
+        body: vec![Statement::PushList {
+            ident: "console".to_string(),
+            value: Expr::Concat(
+                Box::new(Expr::String(format!("{} = ", dbg_label(src_id, src, &span)))),
+                Box::new(Expr::Variable { ident: DBG_ARG.to_string() }),
+            ),
+        }],
+    }
+}
+
+/// Prints the rendered value of a `dbg` call
+fn tdbg(span: &Span, rendered: Expr, stmts: &mut Vec<Statement>) {
+    stmts.push(Statement::SetVar { ident: DBG_ARG.to_string(), value: rendered });
+    stmts.push(Statement::CallProcedure { ident: span_ident(DBG_PREFIX, span) });
+}
+
+/// Generates the procedure that panics with the condition of a failed `assert` (that doesn't have a message)
+fn assert_procedure(src: &str, span: Span) -> Procedure {
+    Procedure {
+        ident: span_ident(ASSERT_PREFIX, &span),
+        body: vec![
+            Statement::SetVar { ident: PANIC_NAME.to_string(), value: Expr::String(format!("assertion failed at <{span:?}>: {}", &src[span.clone()])) },
+            Statement::CallProcedure { ident: "$panic".to_string() },
+        ],
+    }
+}
+
+/// The character lookup procedures, lists and backdrops generated for a charset
+struct CharsetTables {
+    procedures: Vec<Procedure>,
+    lists: Vec<(String, Vec<String>)>,
+    backdrops: Vec<String>,
+}
+
+/// Generates the character lookup procedures, lists and backdrops for a charset
+fn tcharset(charset: &[RangeInclusive<u32>]) -> CharsetTables {
+    // collect all the valid characters of the charset (in order and without duplicates)
+    let chars = charset.iter()
+        .flat_map(|range| range.clone())
+        .filter_map(char::from_u32)
+        .collect::<BTreeSet<_>>();
+
+    let mut list = Vec::new();
+    let mut codes = Vec::new();
+    let mut search = Vec::new();
+    let mut cased = Vec::new();
+    let mut backdrops = Vec::new();
+    for c in chars {
+        list.push(c.to_string());
+        codes.push((c as u32).to_string());
+
+        // characters that scratch would confuse with their lower-case counterpart
+        if c.to_lowercase().ne(std::iter::once(c)) {
+            search.push(CHARSET_MASK.to_string());
+            cased.push((c as u32).to_string());
+            backdrops.push(c.to_string());
+        } else {
+            search.push(c.to_string());
+        }
+    }
+
+    let procedures = vec![
+        // char_code
+        Procedure {
+            ident: CHAR_CODE.to_string(),
+            body: vec![
+                // reset the backdrop then try switch to the backdrop of the character (only succeeds for upper-case characters)
+                Statement::SwitchBackdrop { backdrop: Expr::String(DEFAULT_BACKDROP.to_string()) },
+                Statement::SwitchBackdrop { backdrop: Expr::Variable { ident: CHAR_CODE_ARG.to_string() } },
+                Statement::IfElse {
+                    // number-like characters switch to backdrops by their index instead, so also check the name
+                    condition: Condition::And(
+                        Box::new(Condition::GreaterThan(Expr::BackdropNumber, Expr::PosInteger(1))),
+                        Box::new(Condition::EqualTo(Expr::BackdropName, Expr::Variable { ident: CHAR_CODE_ARG.to_string() })),
+                    ),
+                    body: vec![
+                        Statement::SetVar {
+                            ident: CHAR_CODE_RETURN.to_string(),
+                            value: Expr::ListElement { ident: CHARSET_CASED.to_string(), idx: Box::new(Expr::Sub(Box::new(Expr::BackdropNumber), Box::new(Expr::PosInteger(1)))) },
+                        },
+                    ],
+                    otherwise: vec![
+                        // otherwise search for it
+                        Statement::SetVar {
+                            ident: CHAR_CODE_RETURN.to_string(),
+                            value: Expr::ListItemNum { ident: CHARSET_SEARCH.to_string(), item: Box::new(Expr::Variable { ident: CHAR_CODE_ARG.to_string() }) },
+                        },
+                        Statement::IfElse {
+                            condition: Condition::EqualTo(Expr::Variable { ident: CHAR_CODE_RETURN.to_string() }, Expr::PosInteger(0)),
+                            // characters not in the charset have a code of `-1`
+                            body: vec![Statement::SetVar { ident: CHAR_CODE_RETURN.to_string(), value: Expr::Integer(-1) }],
+                            otherwise: vec![Statement::SetVar {
+                                ident: CHAR_CODE_RETURN.to_string(),
+                                value: Expr::ListElement { ident: CHARSET_CODES.to_string(), idx: Box::new(Expr::Variable { ident: CHAR_CODE_RETURN.to_string() }) },
+                            }],
+                        },
+                    ],
+                },
+                Statement::SwitchBackdrop { backdrop: Expr::String(DEFAULT_BACKDROP.to_string()) },
+            ],
+        },
+        // from_char_code (codes not in the charset produce an empty string)
+        Procedure {
+            ident: FROM_CHAR_CODE.to_string(),
+            body: vec![
+                Statement::SetVar {
+                    ident: FROM_CHAR_CODE_RETURN.to_string(),
+                    value: Expr::ListElement {
+                        ident: CHARSET.to_string(),
+                        idx: Box::new(Expr::ListItemNum { ident: CHARSET_CODES.to_string(), item: Box::new(Expr::Variable { ident: FROM_CHAR_CODE_ARG.to_string() }) }),
+                    },
+                },
+            ],
+        },
+    ];
+
+    let lists = vec![
+        (CHARSET.to_string(), list),
+        (CHARSET_CODES.to_string(), codes),
+        (CHARSET_SEARCH.to_string(), search),
+        (CHARSET_CASED.to_string(), cased),
+    ];
+
+    CharsetTables { procedures, lists, backdrops }
+}
+
 /// Translates a statement
 pub fn tstmt(stmt: TStmt, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) {
     match stmt {
         TStmt::Expr(expr) => {texpr(expr, stmts, tmp_binds);},
-        TStmt::VarDeclare { ident, value } => {
+        TStmt::VarDeclare { ident, value, .. } => {
             // if it's a list then declare the list by making an empty list then setting all the values in it
             if let Type::List(_) = value.1 {
                 // translate the list to a var-get
@@ -203,12 +446,138 @@ pub fn tstmt(stmt: TStmt, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) {
     };
 }
 
+/// Generates the procedure that reverses the `$list_reverse$list` list in-place
+fn list_reverse_procedure() -> Procedure {
+    let var = |ident: &str| Expr::Variable { ident: ident.to_string() };
+    let item = |idx: &str| Expr::ListElement { ident: LIST_REVERSE_LIST.to_string(), idx: Box::new(var(idx)) };
+
+    Procedure {
+        ident: LIST_REVERSE.to_string(),
+        body: vec![
+            // start at both ends of the list
+            Statement::SetVar { ident: LIST_REVERSE_I.to_string(), value: Expr::PosInteger(1) },
+            Statement::SetVar { ident: LIST_REVERSE_J.to_string(), value: Expr::ListLength { ident: LIST_REVERSE_LIST.to_string() } },
+
+            // swap the elements at both ends until they meet in the middle
+            Statement::RepeatUntil {
+                condition: Condition::Not(Box::new(Condition::LessThan(var(LIST_REVERSE_I), var(LIST_REVERSE_J)))),
+                body: vec![
+                    Statement::SetVar { ident: LIST_REVERSE_TMP.to_string(), value: item(LIST_REVERSE_I) },
+                    Statement::ReplaceList { ident: LIST_REVERSE_LIST.to_string(), value: item(LIST_REVERSE_J), idx: var(LIST_REVERSE_I) },
+                    Statement::ReplaceList { ident: LIST_REVERSE_LIST.to_string(), value: var(LIST_REVERSE_TMP), idx: var(LIST_REVERSE_J) },
+                    Statement::SetVar { ident: LIST_REVERSE_I.to_string(), value: Expr::Add(Box::new(var(LIST_REVERSE_I)), Box::new(Expr::PosInteger(1))) },
+                    Statement::SetVar { ident: LIST_REVERSE_J.to_string(), value: Expr::Sub(Box::new(var(LIST_REVERSE_J)), Box::new(Expr::PosInteger(1))) },
+                ],
+            },
+        ],
+    }
+}
+
+/// Generates the procedure that sorts the `$list_sort$list` list in-place (with a stable insertion sort)
+fn list_sort_procedure() -> Procedure {
+    let var = |ident: &str| Expr::Variable { ident: ident.to_string() };
+    let item = |idx: Expr| Expr::ListElement { ident: LIST_SORT_LIST.to_string(), idx: Box::new(idx) };
+    let j_plus = || Expr::Add(Box::new(var(LIST_SORT_J)), Box::new(Expr::PosInteger(1)));
+
+    Procedure {
+        ident: LIST_SORT.to_string(),
+        body: vec![
+            Statement::SetVar { ident: LIST_SORT_I.to_string(), value: Expr::PosInteger(2) },
+            Statement::RepeatUntil {
+                condition: Condition::GreaterThan(var(LIST_SORT_I), Expr::ListLength { ident: LIST_SORT_LIST.to_string() }),
+                body: vec![
+                    // take the next element
+                    Statement::SetVar { ident: LIST_SORT_KEY.to_string(), value: item(var(LIST_SORT_I)) },
+                    Statement::SetVar { ident: LIST_SORT_J.to_string(), value: Expr::Sub(Box::new(var(LIST_SORT_I)), Box::new(Expr::PosInteger(1))) },
+
+                    // shift the larger elements of the sorted section along
+                    Statement::RepeatUntil {
+                        condition: Condition::Not(Box::new(Condition::And(
+                            Box::new(Condition::GreaterThan(var(LIST_SORT_J), Expr::PosInteger(0))),
+                            Box::new(Condition::GreaterThan(item(var(LIST_SORT_J)), var(LIST_SORT_KEY))),
+                        ))),
+                        body: vec![
+                            Statement::ReplaceList { ident: LIST_SORT_LIST.to_string(), value: item(var(LIST_SORT_J)), idx: j_plus() },
+                            Statement::SetVar { ident: LIST_SORT_J.to_string(), value: Expr::Sub(Box::new(var(LIST_SORT_J)), Box::new(Expr::PosInteger(1))) },
+                        ],
+                    },
+
+                    // insert the element into the gap
+                    Statement::ReplaceList { ident: LIST_SORT_LIST.to_string(), value: var(LIST_SORT_KEY), idx: j_plus() },
+                    Statement::SetVar { ident: LIST_SORT_I.to_string(), value: Expr::Add(Box::new(var(LIST_SORT_I)), Box::new(Expr::PosInteger(1))) },
+                ],
+            },
+        ],
+    }
+}
+
+/// Copies the contents of one list to another (wiping the destination list beforehand)
+fn copy_list(from: &str, to: &str, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) {
+    // wipe the destination list
+    stmts.push(Statement::ClearList { ident: to.to_string() });
+
+    // generate a temporary binding for the loop index
+    *tmp_binds += 1;
+    let loop_idx = get_tmp_binds_id(*tmp_binds);
+    stmts.push(Statement::SetVar { ident: loop_idx.clone(), value: Expr::PosInteger(1) }); // lists start at 1
+
+    // loop through the list and append the elements
+    stmts.push(Statement::RepeatUntil {
+        condition: Condition::GreaterThan(Expr::Variable { ident: loop_idx.clone() }, Expr::ListLength { ident: from.to_string() }),
+        body: vec![
+            Statement::PushList {
+                ident: to.to_string(),
+                value: Expr::ListElement { ident: from.to_string(), idx: Box::new(Expr::Variable { ident: loop_idx.clone() }) },
+            },
+            Statement::SetVar { ident: loop_idx.clone(), value: Expr::Add(Box::new(Expr::Variable { ident: loop_idx }), Box::new(Expr::PosInteger(1))) },
+        ],
+    });
+}
+
 /// Get a unique var name from a temporary bindings index
 #[inline]
 fn get_tmp_binds_id(tmp_binds: usize) -> String { // might cause performance issues where there are too many variables and lists
     format!("%{tmp_binds}")
 }
 
+/// Stores an operand in a temporary binding if the operands translated after it (from `at` onwards) generated statements
+///
+/// (those statements run before the whole expr is evaluated, so they could otherwise change the operand, like in `list_get!(list, 0) + list_remove!(list, 0)`)
+fn spill(expr: Expr, at: usize, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> Expr {
+    if stmts.len() == at || matches!(expr, Expr::Float(_) | Expr::String(_) | Expr::Integer(_) | Expr::PosInteger(_)) {
+        return expr;
+    }
+
+    *tmp_binds += 1;
+    let ident = get_tmp_binds_id(*tmp_binds);
+    stmts.insert(at, Statement::SetVar { ident: ident.clone(), value: expr });
+    Expr::Variable { ident }
+}
+
+/// Stores a condition operand in a temporary binding (like `spill`)
+fn spill_cond(cond: Condition, at: usize, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> Condition {
+    if stmts.len() == at {
+        return cond;
+    }
+
+    let value = spill(Expr::Condition(Box::new(cond)), at, stmts, tmp_binds);
+    Condition::EqualTo(value, Expr::String("true".to_string()))
+}
+
+/// Copies a list operand to a temporary list if the operands translated after it (from `at` onwards) generated statements (like `spill`)
+fn spill_list(list: String, at: usize, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> String {
+    if stmts.len() == at {
+        return list;
+    }
+
+    *tmp_binds += 1;
+    let ident = get_tmp_binds_id(*tmp_binds);
+    let mut copy = Vec::new();
+    copy_list(&list, &ident, &mut copy, tmp_binds);
+    stmts.splice(at..at, copy);
+    ident
+}
+
 /// Translates a list (creates a temporary bind) and returns the name of that binding
 pub fn tlist(list: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> String {
     match list {
@@ -239,12 +608,30 @@ pub fn tlist(list: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             // return the return list identifier
             "$return".to_string()
         },
+        // a `dbg` call prints the list and then returns it
+        TExpr::BuiltinFnCall(call) if matches!(*call, TBuiltinFnCall::Dbg { .. }) => {
+            let TBuiltinFnCall::Dbg { span, expr: ((expr, _), var_type) } = *call else { unreachable!() };
+            let list = tlist(expr, stmts, tmp_binds);
+            let rendered = tstr(TExpr::VarGet { ident: list.clone(), var_type: var_type.clone() }, &var_type, true, stmts, tmp_binds);
+            tdbg(&span, rendered, stmts);
+            list
+        },
 
         // no support for anything else yet
         _ => unreachable!(),
     }
 }
 
+/// Translates an operand of `==` or `!=` into a value that can be compared
+///
+/// (lists aren't values in scratch, so they're compared by their rendered strings, like tuples already are)
+fn tvalue(((expr, _), etype): Typed<Spanned<TExpr>>, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> Expr {
+    match etype {
+        Type::List(_) => tstr(expr, &etype, true, stmts, tmp_binds),
+        _ => texpr(expr, stmts, tmp_binds),
+    }
+}
+
 /// Translates a condition
 pub fn tcond(cond: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> Condition {
     match cond {
@@ -260,30 +647,40 @@ pub fn tcond(cond: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
         TExpr::VarGet { ident, .. } => Condition::EqualTo(Expr::Variable { ident }, Expr::String("true".to_string())),
 
         TExpr::EE(lhs, rhs) => {
-            let lhs = texpr(lhs.0.0, stmts, tmp_binds);
-            let rhs = texpr(rhs.0.0, stmts, tmp_binds);
+            let lhs = tvalue(*lhs, stmts, tmp_binds);
+            let at = stmts.len();
+            let rhs = tvalue(*rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Condition::EqualTo(lhs, rhs)
         },
         TExpr::NE(lhs, rhs) => {
-            let lhs = texpr(lhs.0.0, stmts, tmp_binds);
-            let rhs = texpr(rhs.0.0, stmts, tmp_binds);
+            let lhs = tvalue(*lhs, stmts, tmp_binds);
+            let at = stmts.len();
+            let rhs = tvalue(*rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Condition::Not(Box::new(Condition::EqualTo(lhs, rhs)))
         },
 
         TExpr::GT(lhs, rhs) => {
             let lhs = texpr(lhs.0.0, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs.0.0, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Condition::GreaterThan(lhs, rhs)
         },
         TExpr::LT(lhs, rhs) => {
             let lhs = texpr(lhs.0.0, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs.0.0, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Condition::LessThan(lhs, rhs)
         },
 
         TExpr::GTE(lhs, rhs) => {
             let lhs = texpr(lhs.0.0, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs.0.0, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Condition::Or(
                 Box::new(Condition::GreaterThan(lhs.clone(), rhs.clone())),
                 Box::new(Condition::EqualTo(lhs, rhs)),
@@ -291,7 +688,9 @@ pub fn tcond(cond: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
         },
         TExpr::LTE(lhs, rhs) => {
             let lhs = texpr(lhs.0.0, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs.0.0, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Condition::Or(
                 Box::new(Condition::LessThan(lhs.clone(), rhs.clone())),
                 Box::new(Condition::EqualTo(lhs, rhs)),
@@ -300,12 +699,16 @@ pub fn tcond(cond: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
 
         TExpr::And(lhs, rhs) => {
             let lhs = tcond(lhs.0.0, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = tcond(rhs.0.0, stmts, tmp_binds);
+            let lhs = spill_cond(lhs, at, stmts, tmp_binds);
             Condition::And(Box::new(lhs), Box::new(rhs))
         },
         TExpr::Or(lhs, rhs) => {
             let lhs = tcond(lhs.0.0, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = tcond(rhs.0.0, stmts, tmp_binds);
+            let lhs = spill_cond(lhs, at, stmts, tmp_binds);
             Condition::Or(Box::new(lhs), Box::new(rhs))
         },
 
@@ -314,7 +717,108 @@ pub fn tcond(cond: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             Condition::Not(Box::new(cond))
         },
 
-        _ => panic!("should be covered by the type system (must be valid)"),
+        // any other boolean expr (function calls, blocks, builtins) is compared against it's string value
+        cond => Condition::EqualTo(texpr(cond, stmts, tmp_binds), Expr::String("true".to_string())),
+    }
+}
+
+/// Translates an expr into a rust-debug-like string representation of it
+///
+/// (strings are only wrapped in quotes when `quoted`, like when they're within lists or tuples)
+pub fn tstr(expr: TExpr, etype: &Type, quoted: bool, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) -> Expr {
+    let concat = |lhs: Expr, rhs: Expr| Expr::Concat(Box::new(lhs), Box::new(rhs));
+
+    match (expr, etype) {
+        // tuples are rendered element-by-element
+        (TExpr::Tuple(exprs), _) => {
+            let mut string = Expr::String("(".to_string());
+            for (i, ((expr, _), etype)) in exprs.into_iter().enumerate() {
+                if i != 0 {
+                    string = concat(string, Expr::String(", ".to_string()));
+                }
+                let at = stmts.len();
+                let element = tstr(expr, &etype, true, stmts, tmp_binds);
+                string = concat(spill(string, at, stmts, tmp_binds), element);
+            }
+
+            concat(string, Expr::String(")".to_string()))
+        },
+
+        // blocks are rendered by their tail
+        (TExpr::Block(block), _) => {
+            // append all of the block's statements
+            for ((stmt, _), _) in block.stmts {
+                tstmt(stmt, stmts, tmp_binds);
+            }
+
+            match block.tail {
+                Some(((TStmt::Expr(tail), _), ttype)) => tstr(tail, &ttype, quoted, stmts, tmp_binds),
+                Some(((stmt, _), _)) => {
+                    tstmt(stmt, stmts, tmp_binds);
+                    Expr::String("()".to_string())
+                },
+                None => Expr::String("()".to_string()),
+            }
+        },
+
+        // any other tuple (vars, calls, etc.) already is it's rendered string
+        (expr, Type::Tuple(_)) => texpr(expr, stmts, tmp_binds),
+
+        // lists are joined with a loop
+        (list, Type::List(el_type)) => {
+            // translate the list to a var-get
+            let list = tlist(list, stmts, tmp_binds);
+
+            // generate temporary bindings for the resulting string and the loop index
+            *tmp_binds += 1;
+            let string = get_tmp_binds_id(*tmp_binds);
+            *tmp_binds += 1;
+            let loop_idx = get_tmp_binds_id(*tmp_binds);
+            stmts.push(Statement::SetVar { ident: string.clone(), value: Expr::String("[".to_string()) });
+            stmts.push(Statement::SetVar { ident: loop_idx.clone(), value: Expr::PosInteger(1) }); // lists start at 1
+
+            // render the element (the elements are already stored as scratch values)
+            let element = Expr::ListElement { ident: list.clone(), idx: Box::new(Expr::Variable { ident: loop_idx.clone() }) };
+            let element = match **el_type {
+                Type::String => concat(concat(Expr::String("\"".to_string()), element), Expr::String("\"".to_string())),
+                Type::Nil => Expr::String("()".to_string()),
+                _ => element,
+            };
+
+            // loop through the list and append the elements
+            stmts.push(Statement::RepeatUntil {
+                condition: Condition::GreaterThan(Expr::Variable { ident: loop_idx.clone() }, Expr::ListLength { ident: list }),
+                body: vec![
+                    // separate the elements with commas
+                    Statement::If {
+                        condition: Condition::GreaterThan(Expr::Variable { ident: loop_idx.clone() }, Expr::PosInteger(1)),
+                        body: vec![Statement::SetVar { ident: string.clone(), value: concat(Expr::Variable { ident: string.clone() }, Expr::String(", ".to_string())) }],
+                    },
+                    Statement::SetVar { ident: string.clone(), value: concat(Expr::Variable { ident: string.clone() }, element) },
+                    // update the index
+                    Statement::SetVar { ident: loop_idx.clone(), value: Expr::Add(Box::new(Expr::Variable { ident: loop_idx }), Box::new(Expr::PosInteger(1))) },
+                ],
+            });
+
+            // close the list
+            stmts.push(Statement::SetVar { ident: string.clone(), value: concat(Expr::Variable { ident: string.clone() }, Expr::String("]".to_string())) });
+            Expr::Variable { ident: string }
+        },
+
+        // nil is always `()` (though the expr still needs to be evaluated)
+        (expr, Type::Nil) => {
+            texpr(expr, stmts, tmp_binds);
+            Expr::String("()".to_string())
+        },
+
+        // strings within lists and tuples are quoted
+        (expr, Type::String) if quoted => concat(
+            concat(Expr::String("\"".to_string()), texpr(expr, stmts, tmp_binds)),
+            Expr::String("\"".to_string()),
+        ),
+
+        // everything else (numbers, booleans and top-level strings) are already strings in scratch
+        (expr, _) => texpr(expr, stmts, tmp_binds),
     }
 }
 
@@ -347,7 +851,32 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             use crate::lang::typed::builtin::TBuiltinFnCall as B;
             match *call {
                 // convert the `as_str` builtin to it's scratch counterpart
-                B::AsString((expr, _)) => texpr(expr, stmts, tmp_binds),
+                B::AsString((expr, _), etype) => tstr(expr, &etype, false, stmts, tmp_binds),
+
+                // lists are printed by `tlist` (and aren't values here, like list literals)
+                B::Dbg { span, expr } if matches!(expr.1, Type::List(_)) => {
+                    tlist(TExpr::BuiltinFnCall(Box::new(B::Dbg { span, expr })), stmts, tmp_binds);
+                    Expr::String("<list>".to_string())
+                },
+                // everything else is stored in a temporary binding, so it's only evaluated once
+                B::Dbg { span, expr: ((expr, _), etype) } => {
+                    let value = texpr(expr, stmts, tmp_binds);
+                    *tmp_binds += 1;
+                    let ident = get_tmp_binds_id(*tmp_binds);
+                    stmts.push(Statement::SetVar { ident: ident.clone(), value });
+
+                    let value = Expr::Variable { ident };
+                    let rendered = match etype {
+                        Type::String => Expr::Concat(
+                            Box::new(Expr::Concat(Box::new(Expr::String("\"".to_string())), Box::new(value.clone()))),
+                            Box::new(Expr::String("\"".to_string())),
+                        ),
+                        Type::Nil => Expr::String("()".to_string()),
+                        _ => value.clone(),
+                    };
+                    tdbg(&span, rendered, stmts);
+                    value
+                },
 
                 // convert the `input` builtin to it's scratch counterpart
                 B::Input((expr, _)) => {
@@ -391,6 +920,46 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                     Expr::String(NIL.to_string())
                 },
 
+                // convert the `assert` builtin to a panic if the condition is false
+                B::Assert { span, cond, message } => {
+                    let cond = tcond(cond.0, stmts, tmp_binds);
+                    let body = match message {
+                        Some(message) => {
+                            let message = Expr::Concat(
+                                Box::new(Expr::String(format!("assertion failed at <{span:?}>: "))),
+                                Box::new(texpr(message.0, stmts, tmp_binds)),
+                            );
+                            vec![
+                                Statement::SetVar { ident: PANIC_NAME.to_string(), value: message },
+                                Statement::CallProcedure { ident: "$panic".to_string() },
+                            ]
+                        },
+                        // the message is the condition's source code, which only `translate` knows
+                        None => vec![Statement::CallProcedure { ident: span_ident(ASSERT_PREFIX, &span) }],
+                    };
+
+                    stmts.push(Statement::If {
+                        condition: Condition::Not(Box::new(cond)),
+                        body,
+                    });
+
+                    Expr::String(NIL.to_string())
+                },
+
+                // convert the `assert_eq` builtin to a panic if both sides aren't equal
+                B::AssertEq { span, lhs, rhs } => {
+                    let cond = tcond(TExpr::EE(Box::new(lhs), Box::new(rhs)), stmts, tmp_binds);
+                    stmts.push(Statement::If {
+                        condition: Condition::Not(Box::new(cond)),
+                        body: vec![
+                            Statement::SetVar { ident: PANIC_NAME.to_string(), value: Expr::String(format!("assertion `left == right` failed at <{span:?}>")) },
+                            Statement::CallProcedure { ident: "$panic".to_string() },
+                        ],
+                    });
+
+                    Expr::String(NIL.to_string())
+                },
+
                 // list builtin-funcs
 
                 // convert the `list_len` builtin to it's scratch counterpart
@@ -423,7 +992,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                     let list = tlist(list.0, stmts, tmp_binds);
                     
                     // translate the list idx (+1 due to their lists indexs starting at 1 instead of 0)
+                    let at = stmts.len();
                     let idx = texpr(idx.0, stmts, tmp_binds);
+                    let list = spill_list(list, at, stmts, tmp_binds);
                     let idx_plus = Expr::Add(Box::new(idx.clone()), Box::new(Expr::PosInteger(1)));
 
                     // add bounds checking statement (negative)
@@ -523,11 +1094,13 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                         ],
                     });
 
-                    // remove the item at that index
+                    // keep the item before removing it, so it can be returned
+                    *tmp_binds += 1;
+                    let item = get_tmp_binds_id(*tmp_binds);
+                    stmts.push(Statement::SetVar { ident: item.clone(), value: Expr::ListElement { ident: list.clone(), idx: Box::new(idx_plus.clone()) } });
                     stmts.push(Statement::RemoveList { ident: list, idx: idx_plus });
 
-                    // return nill
-                    Expr::String(NIL.to_string())
+                    Expr::Variable { ident: item }
                 },
 
                 // convert the `list_len` builtin to it's scratch counterpart
@@ -558,10 +1131,10 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                         ],
                     });
 
-                    // add bounds checking statement (larger than length)
+                    // add bounds checking statement (larger than length, as inserting at the length appends the item)
                     stmts.push(Statement::If {
                         condition: Condition::GreaterThan(
-                            idx_plus.clone(),
+                            idx.clone(),
                             Expr::ListLength { ident: list.clone() },
                         ),
                         body: vec![
@@ -656,6 +1229,68 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                     Expr::String(NIL.to_string())
                 },
 
+                // convert the `list_contains` builtin to it's scratch counterpart
+                B::ListContains { list, expr } => {
+                    // translate the expr & list
+                    let list = tlist(list.0, stmts, tmp_binds);
+                    let at = stmts.len();
+                    let item = texpr(expr.0, stmts, tmp_binds);
+                    let list = spill_list(list, at, stmts, tmp_binds);
+
+                    // return the condition as an expr
+                    Expr::Condition(Box::new(Condition::ListContains { ident: list, item }))
+                },
+
+                // convert the `list_index_of` builtin to it's scratch counterpart
+                B::ListIndexOf { list, expr } => {
+                    // translate the expr & list
+                    let list = tlist(list.0, stmts, tmp_binds);
+                    let at = stmts.len();
+                    let item = texpr(expr.0, stmts, tmp_binds);
+                    let list = spill_list(list, at, stmts, tmp_binds);
+
+                    // -1 due to their lists indexs starting at 1 instead of 0 (which also makes missing items -1)
+                    Expr::Sub(
+                        Box::new(Expr::ListItemNum { ident: list, item: Box::new(item) }),
+                        Box::new(Expr::PosInteger(1)),
+                    )
+                },
+
+                // convert the `list_clear` builtin to it's scratch counterpart
+                B::ListClear(list) => {
+                    let list = tlist(list.0, stmts, tmp_binds);
+                    stmts.push(Statement::ClearList { ident: list });
+
+                    // return nill
+                    Expr::String(NIL.to_string())
+                },
+
+                // convert the `list_reverse` builtin to a call to the reverse procedure
+                B::ListReverse(list) => {
+                    let list = tlist(list.0, stmts, tmp_binds);
+
+                    // copy the list to the procedure, reverse it, then copy it back
+                    copy_list(&list, LIST_REVERSE_LIST, stmts, tmp_binds);
+                    stmts.push(Statement::CallProcedure { ident: LIST_REVERSE.to_string() });
+                    copy_list(LIST_REVERSE_LIST, &list, stmts, tmp_binds);
+
+                    // return nill
+                    Expr::String(NIL.to_string())
+                },
+
+                // convert the `list_sort` builtin to a call to the sort procedure
+                B::ListSort(list) => {
+                    let list = tlist(list.0, stmts, tmp_binds);
+
+                    // copy the list to the procedure, sort it, then copy it back
+                    copy_list(&list, LIST_SORT_LIST, stmts, tmp_binds);
+                    stmts.push(Statement::CallProcedure { ident: LIST_SORT.to_string() });
+                    copy_list(LIST_SORT_LIST, &list, stmts, tmp_binds);
+
+                    // return nill
+                    Expr::String(NIL.to_string())
+                },
+
                 // string operations
 
                 // convert the `str_len` builtin to it's scratch counterpart
@@ -667,8 +1302,10 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                 },
                 B::StringGet { string, idx, .. } => {
                     // translate the string and index exprs
-                    let idx = texpr(idx.0, stmts, tmp_binds);
                     let expr = texpr(string.0, stmts, tmp_binds);
+                    let at = stmts.len();
+                    let idx = texpr(idx.0, stmts, tmp_binds);
+                    let expr = spill(expr, at, stmts, tmp_binds);
 
                     // increment the idx by one (strings and lists are 1-based)
                     let idx = Expr::Add(Box::new(idx), Box::new(Expr::PosInteger(1)));
@@ -676,6 +1313,62 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
                     // return the operation on that string
                     Expr::StringElement { string: Box::new(expr), idx: Box::new(idx) }
                 },
+
+                // convert the `format` builtin to a chain of concatinations
+                B::Format(segments) => {
+                    let mut string = None;
+                    for segment in segments {
+                        let at = stmts.len();
+                        let segment = match segment {
+                            FormatSegment::Literal(literal) => Expr::String(literal),
+                            FormatSegment::Arg((expr, _), etype) => tstr(expr, &etype, false, stmts, tmp_binds),
+                        };
+
+                        // the string so far is spilled if the argument generated statements
+                        string = Some(match string.map(|string| spill(string, at, stmts, tmp_binds)) {
+                            Some(string) => Expr::Concat(Box::new(string), Box::new(segment)),
+                            None => segment,
+                        });
+                    }
+
+                    string.unwrap_or_else(|| Expr::String(String::new()))
+                },
+
+                // character code operations
+
+                // convert the `char_code` builtin to a call to the lookup procedure
+                B::CharCode { string, idx } => {
+                    // translate the string and index exprs
+                    let string = texpr(string.0, stmts, tmp_binds);
+                    let at = stmts.len();
+                    let idx = texpr(idx.0, stmts, tmp_binds);
+                    let string = spill(string, at, stmts, tmp_binds);
+
+                    // pass the character to the procedure (strings are 1-based)
+                    let idx = Expr::Add(Box::new(idx), Box::new(Expr::PosInteger(1)));
+                    stmts.push(Statement::SetVar { ident: CHAR_CODE_ARG.to_string(), value: Expr::StringElement { string: Box::new(string), idx: Box::new(idx) } });
+                    stmts.push(Statement::CallProcedure { ident: CHAR_CODE.to_string() });
+
+                    // copy the result to a temporary binding so it isn't overwritten by later calls
+                    *tmp_binds += 1;
+                    let result = get_tmp_binds_id(*tmp_binds);
+                    stmts.push(Statement::SetVar { ident: result.clone(), value: Expr::Variable { ident: CHAR_CODE_RETURN.to_string() } });
+                    Expr::Variable { ident: result }
+                },
+
+                // convert the `from_char_code` builtin to a call to the lookup procedure
+                B::FromCharCode(code) => {
+                    // pass the code to the procedure
+                    let code = texpr(code.0, stmts, tmp_binds);
+                    stmts.push(Statement::SetVar { ident: FROM_CHAR_CODE_ARG.to_string(), value: code });
+                    stmts.push(Statement::CallProcedure { ident: FROM_CHAR_CODE.to_string() });
+
+                    // copy the result to a temporary binding so it isn't overwritten by later calls
+                    *tmp_binds += 1;
+                    let result = get_tmp_binds_id(*tmp_binds);
+                    stmts.push(Statement::SetVar { ident: result.clone(), value: Expr::Variable { ident: FROM_CHAR_CODE_RETURN.to_string() } });
+                    Expr::Variable { ident: result }
+                },
             }
         },
 
@@ -733,7 +1426,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             let (((lhs, _), _), ((rhs, _), _)) = (*lhs, *rhs);
 
             let lhs = texpr(lhs, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
 
             Expr::Concat(Box::new(lhs), Box::new(rhs))
         },
@@ -750,7 +1445,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             let (((lhs, _), _), ((rhs, _), _)) = (*lhs, *rhs);
 
             let lhs = texpr(lhs, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
 
             Expr::Add(Box::new(lhs), Box::new(rhs))
         },
@@ -758,7 +1455,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             let (((lhs, _), _), ((rhs, _), _)) = (*lhs, *rhs);
 
             let lhs = texpr(lhs, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
 
             Expr::Sub(Box::new(lhs), Box::new(rhs))
         },
@@ -766,7 +1465,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             let (((lhs, _), _), ((rhs, _), _)) = (*lhs, *rhs);
 
             let lhs = texpr(lhs, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
 
             Expr::Mul(Box::new(lhs), Box::new(rhs))
         },
@@ -774,7 +1475,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             let (((lhs, _), _), ((rhs, _), _)) = (*lhs, *rhs);
 
             let lhs = texpr(lhs, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
 
             Expr::Div(Box::new(lhs), Box::new(rhs))
         },
@@ -782,7 +1485,9 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
             let (((lhs, _), _), ((rhs, _), _)) = (*lhs, *rhs);
 
             let lhs = texpr(lhs, stmts, tmp_binds);
+            let at = stmts.len();
             let rhs = texpr(rhs, stmts, tmp_binds);
+            let lhs = spill(lhs, at, stmts, tmp_binds);
             Expr::Mod(Box::new(lhs), Box::new(rhs))
         },
 
@@ -811,6 +1516,10 @@ pub fn texpr(expr: TExpr, stmts: &mut Vec<Statement>, tmp_binds: &mut usize) ->
         E::Or(lhs, rhs) => Expr::Condition(Box::new(tcond(TExpr::Or(lhs, rhs), stmts, tmp_binds))),
         E::Not(cond) => Expr::Condition(Box::new(tcond(TExpr::Not(cond), stmts, tmp_binds))),
 
-        _ => todo!(),
+        // tuples aren't values in scratch, so they're stored as their rendered string
+        E::Tuple(exprs) => {
+            let ttype = Type::Tuple(exprs.iter().map(|(_, etype)| etype.clone()).collect());
+            tstr(E::Tuple(exprs), &ttype, true, stmts, tmp_binds)
+        },
     }
 }
@@ -1,22 +1,45 @@
-use logos::Logos;
-use crate::lang::error::parser::Error;
+use logos::{FilterResult, Lexer, Logos};
+use crate::lang::{edition::Edition, error::parser::Error};
+
+/// The state of the lexer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LexerState {
+    /// The edition the source is lexed with
+    pub edition: Edition,
+    /// How deeply the lexer is nested within parentheses, brackets & braces (doc comments are only tokens outside of them)
+    depth: usize,
+}
+
+impl From<Edition> for LexerState {
+    fn from(edition: Edition) -> Self {
+        Self { edition, depth: 0 }
+    }
+}
 
 /// A token generated by the lexer
 #[derive(Debug, Clone, Logos, PartialEq)]
 #[logos(error = Error)]
+#[logos(extras = LexerState)]
 #[logos(skip r"[ \t\r\n\f]+")] // whitespace
 #[logos(skip r"\/\/[^\n\r]*")] // `//` comments
 #[logos(skip r"#[^\n\r]*")] // `#` comments
-#[logos(skip r"\/\*[^\*\/]*\*\/")] // multi-line comments
 pub enum Token {
+    // Comments
+    #[token("/*", lex_block_comment)]
+    BlockComment, // never actually emitted, as block comments are always skipped
+    #[regex(r"\/\/\/[^\n\r]*", lex_doc_comment)]
+    DocComment(String),
+
     // Literals
-    #[regex(r"[0-9]+", |lex| lex.slice().parse::<f64>().unwrap())]
-    #[regex(r"[0-9]+\.[0-9]+f", |lex| lex.slice()[1..lex.slice().len()-1].parse::<f64>().unwrap())]
-    Number(f64),
-    #[regex(r#""([^"\\]|\\["\\bnfrt]|u[a-fA-F0-9]{4})*""#, |lex| lex.slice()[1..lex.slice().len()-1].to_owned())]
+    #[regex(r"[0-9][0-9_]*(\.[0-9][0-9_]*)?([eE][+-]?[0-9_]*)?", lex_decimal)]
+    #[regex(r"0[xX][0-9a-zA-Z_]*", |lex| lex_radix(lex, 16))]
+    #[regex(r"0[oO][0-9a-zA-Z_]*", |lex| lex_radix(lex, 8))]
+    #[regex(r"0[bB][0-9a-zA-Z_]*", |lex| lex_radix(lex, 2))]
+    Number(f64), // `Infinity` & `NaN` are lexed by `lex_ident`
+    #[token("\"", lex_string)]
+    #[regex("r#*\"", lex_raw_string)]
     String(String),
-    #[regex(r"true|false", |lex| lex.slice() == "true")]
-    Bool(bool),
+    Bool(bool), // lexed by `lex_ident`
 
     // Symbols
     #[token(".")]
@@ -81,41 +104,272 @@ pub enum Token {
     NE,
 
     // Parentheses
-    #[token("(")]
+    #[token("(", open)]
     LParen,
-    #[token(")")]
+    #[token(")", close)]
     RParen,   
 
     // Brackets
-    #[token("[")]
+    #[token("[", open)]
     LBracket,
-    #[token("]")]
+    #[token("]", close)]
     RBracket,
 
     // Braces
-    #[token("{")]
+    #[token("{", open)]
     LBrace,
-    #[token("}")]
+    #[token("}", close)]
     RBrace,
 
-    // Keywords & Idents
-    #[regex(r"[a-zA-Z_][a-zA-Z0-9_-]*", |lex| lex.slice().to_owned())]
+    // Keywords & Idents (all lexed by `lex_ident`)
+    #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", lex_ident)]
+    IdentOrBuiltin, // never actually emitted, as it's always lexed into either an identifier, builtin-func, keyword or literal
     Ident(String),
-    #[regex(r"[a-zA-Z_][a-zA-Z0-9_-]*!", |lex| lex.slice()[..lex.slice().len()-1].to_owned())]
     BuiltinFunc(String),
-    #[token("main")]
     Main,
-    #[token("var")]
     Var,
-    #[token("mut")]
     Mut,
-    #[token("if")]
     If,
-    #[token("else")]
     Else,
-    #[token("while")]
     While,
-    #[token("fn")]
     Func,
 }
 
+
+/// Lexes an identifier or builtin-func (`ident!`), also consuming any `-`s within it in editions with kebab-case identifiers
+///
+/// keywords (and the `true`, `false`, `Infinity` & `NaN` literals) are only lexed when they're the whole identifier,
+/// so `true-count` is still an identifier in editions with kebab-case identifiers
+fn lex_ident(lex: &mut Lexer<Token>) -> Token {
+    if lex.extras.edition.kebab_idents() {
+        let len = lex.remainder()
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(lex.remainder().len());
+        lex.bump(len);
+    }
+
+    // builtin-funcs are identifiers followed by a `!`
+    if lex.remainder().starts_with('!') {
+        lex.bump(1);
+        return Token::BuiltinFunc(lex.slice()[..lex.slice().len()-1].to_owned());
+    }
+
+    match lex.slice() {
+        "main" => Token::Main,
+        "var" => Token::Var,
+        "mut" => Token::Mut,
+        "if" => Token::If,
+        "else" => Token::Else,
+        "while" => Token::While,
+        "fn" => Token::Func,
+        "true" => Token::Bool(true),
+        "false" => Token::Bool(false),
+        "Infinity" => Token::Number(f64::INFINITY),
+        "NaN" => Token::Number(f64::NAN),
+        ident => Token::Ident(ident.to_owned()),
+    }
+}
+
+/// Tracks the opening of parentheses, brackets & braces
+fn open(lex: &mut Lexer<Token>) {
+    lex.extras.depth += 1;
+}
+
+/// Tracks the closing of parentheses, brackets & braces
+fn close(lex: &mut Lexer<Token>) {
+    lex.extras.depth = lex.extras.depth.saturating_sub(1);
+}
+
+/// Skips a (possibly nested) block comment (given that the opening `/*` has already been consumed)
+fn lex_block_comment(lex: &mut Lexer<Token>) -> FilterResult<(), Error> {
+    let src = lex.remainder();
+    let mut depth = 1;
+    let mut i = 0;
+
+    while i < src.len() {
+        if src[i..].starts_with("/*") {
+            depth += 1;
+            i += 2;
+        } else if src[i..].starts_with("*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                lex.bump(i);
+                return FilterResult::Skip;
+            }
+        } else {
+            i += src[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+    }
+
+    // the comment was never closed
+    lex.bump(src.len());
+    FilterResult::Error(Error::UnterminatedComment)
+}
+
+/// Lexes a `///` doc comment into it's contents (`////` and onwards are treated as regular comments)
+///
+/// only roots can be documented, so doc comments within parentheses, brackets & braces are also regular comments
+fn lex_doc_comment(lex: &mut Lexer<Token>) -> FilterResult<String, Error> {
+    let contents = &lex.slice()[3..];
+    if contents.starts_with('/') || lex.extras.depth > 0 { return FilterResult::Skip }
+    FilterResult::Emit(contents.strip_prefix(' ').unwrap_or(contents).to_string())
+}
+
+/// Lexes a decimal number literal (with optional `_` separators, fractional part and exponent)
+fn lex_decimal(lex: &mut Lexer<Token>) -> Result<f64, Error> {
+    let slice = lex.slice();
+
+    // make sure that the exponent (if any) actually has digits
+    if let Some(exp) = slice.find(['e', 'E']) {
+        if !slice[exp+1..].contains(|c: char| c.is_ascii_digit()) {
+            return Err(Error::ExpectedDigits);
+        }
+    }
+
+    let num = slice.replace('_', "").parse::<f64>().map_err(|_| Error::ExpectedDigits)?;
+    if num.is_infinite() { return Err(Error::NumberOverflow) }
+    Ok(num)
+}
+
+/// Lexes a hexadecimal, octal or binary number literal (with optional `_` separators)
+///
+/// (like decimal literals, it's accumulated as a float, so literals past 64 bits are rounded instead of overflowing)
+fn lex_radix(lex: &mut Lexer<Token>, radix: u32) -> Result<f64, Error> {
+    let start = lex.span().start + 2; // skip the `0x`, `0o` or `0b` prefix
+    let digits = &lex.slice()[2..];
+
+    let mut num: f64 = 0.0;
+    let mut has_digits = false;
+    for (i, c) in digits.char_indices() {
+        if c == '_' { continue }
+        let Some(digit) = c.to_digit(radix)
+        else { return Err(Error::InvalidDigit { digit_span: start + i..start + i + 1, radix }) };
+
+        num = num * radix as f64 + digit as f64;
+        has_digits = true;
+    }
+
+    if !has_digits { return Err(Error::ExpectedDigits) }
+    if num.is_infinite() { return Err(Error::NumberOverflow) }
+    Ok(num)
+}
+
+/// Lexes a string literal and decodes it's escape sequences (given that the opening `"` has already been consumed)
+///
+/// (string literals may span multiple lines, and a `\` at the end of a line skips the newline and the next line's indentation)
+fn lex_string(lex: &mut Lexer<Token>) -> Result<String, Error> {
+    let start = lex.span().end; // the start of the string's contents
+    let src = lex.remainder();
+    let mut string = String::new();
+    let mut error = None;
+    let mut chars = src.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            // end of the string
+            '"' => {
+                lex.bump(i + 1);
+                return match error {
+                    Some(err) => Err(err),
+                    None => Ok(string),
+                };
+            },
+
+            // escape sequences
+            '\\' => {
+                let Some((_, escape)) = chars.next()
+                else { break };
+
+                let decoded = match escape {
+                    'n' => Some('\n'),
+                    't' => Some('\t'),
+                    'r' => Some('\r'),
+                    'b' => Some('\u{8}'),
+                    'f' => Some('\u{c}'),
+                    '0' => Some('\0'),
+                    '"' => Some('"'),
+                    '\'' => Some('\''),
+                    '\\' => Some('\\'),
+
+                    // line continuations (skip the newline and any leading whitespace)
+                    '\n' | '\r' => {
+                        while let Some((_, ' ' | '\t' | '\n' | '\r')) = chars.peek() {
+                            chars.next();
+                        }
+                        continue;
+                    },
+
+                    // unicode escapes (both `\uXXXX` and `\u{X..}`)
+                    'u' => {
+                        let hex = if let Some((_, '{')) = chars.peek() {
+                            chars.next();
+                            let mut hex = String::new();
+                            while let Some(&(_, c)) = chars.peek() {
+                                if !c.is_ascii_hexdigit() { break }
+                                chars.next();
+                                hex.push(c);
+                            }
+
+                            // an unclosed escape leaves the character after it (which may be the closing `"`) to the rest of the string
+                            match chars.peek() {
+                                Some((_, '}')) => { chars.next(); hex },
+                                _ => String::new(),
+                            }
+                        } else {
+                            let mut hex = String::new();
+                            while let Some(&(_, c)) = chars.peek() {
+                                if hex.len() == 4 || !c.is_ascii_hexdigit() { break }
+                                chars.next();
+                                hex.push(c);
+                            }
+                            if hex.len() == 4 { hex } else { String::new() }
+                        };
+
+                        if hex.is_empty() || hex.len() > 6 {
+                            None
+                        } else {
+                            u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                        }
+                    },
+
+                    _ => None,
+                };
+
+                match decoded {
+                    Some(c) => string.push(c),
+                    // only keep the first invalid escape sequence
+                    None if error.is_none() => {
+                        let end = chars.peek().map(|(j, _)| *j).unwrap_or(src.len());
+                        error = Some(Error::InvalidEscape { escape_span: start + i..start + end });
+                    },
+                    None => (),
+                }
+            },
+
+            c => string.push(c),
+        }
+    }
+
+    // the string was never terminated
+    lex.bump(src.len());
+    Err(Error::UnterminatedString)
+}
+
+/// Lexes a raw string literal like `r"..."` or `r#"..."#` (given that the `r`, `#`s and opening `"` have already been consumed)
+fn lex_raw_string(lex: &mut Lexer<Token>) -> Result<String, Error> {
+    let hashes = lex.slice().len() - 2;
+    let terminator = format!("\"{}", "#".repeat(hashes));
+
+    match lex.remainder().find(&terminator) {
+        Some(end) => {
+            let string = lex.remainder()[..end].to_string();
+            lex.bump(end + terminator.len());
+            Ok(string)
+        },
+        None => {
+            lex.bump(lex.remainder().len());
+            Err(Error::UnterminatedString)
+        },
+    }
+}
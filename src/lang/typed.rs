@@ -6,3 +6,4 @@ pub mod block;
 pub mod root;
 pub mod stmt;
 pub mod function;
+pub mod suggest;
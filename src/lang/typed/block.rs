@@ -6,10 +6,14 @@ use super::{stmt::TStmt, symbol_table::{FuncTable, TypeTable, VarTable}, types::
 pub struct TBlock {
     pub stmts: Vec<Typed<Spanned<TStmt>>>,
     pub tail: Option<Typed<Spanned<TStmt>>>,
+    /// The prefix of the identifiers of the variables declared in this block (like `$root$1`)
+    pub scope: String,
 }
 
 /// Adds type annotations to a block
 pub fn wrap_block(block: Block, type_table: &TypeTable, func_table: &FuncTable, mut var_table: VarTable) -> Result<Typed<TBlock>, Error> {
+    let scope = var_table.prefix().to_string();
+
     // iterate through the block's statements and add type annotations to all of them
     let mut stmts = Vec::new();
     for (stmt, span) in block.stmts {
@@ -32,6 +36,7 @@ pub fn wrap_block(block: Block, type_table: &TypeTable, func_table: &FuncTable,
         TBlock {
             stmts,
             tail,
+            scope,
         },
         tail_type,
     ))
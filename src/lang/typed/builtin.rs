@@ -1,12 +1,12 @@
-use ketchup::Span;
-use crate::lang::{error::typed::Error, parser::expr::Expr, typed::{expr::wrap_expr, symbol_table::TypeTable, types::Type}, Spanned};
-use super::{expr::TExpr, symbol_table::{FuncTable, VarTable}, types::Typed};
+use ketchup::{node::Node, Span};
+use crate::lang::{error::{line_column, typed::Error}, parser::expr::{Expr, ExprOper}, typed::{expr::wrap_expr, symbol_table::TypeTable, types::Type}, Spanned};
+use super::{expr::TExpr, suggest, symbol_table::{FuncTable, VarTable}, types::Typed};
 
 /// A tree representation of a builtin-function call
 #[derive(Debug)]
 pub enum TBuiltinFnCall {
     PrintLn(Option<Spanned<TExpr>>),
-    AsString(Spanned<TExpr>),
+    AsString(Spanned<TExpr>, Type),
     Input(Spanned<TExpr>),
     Timer,
     Panic(Span, Option<Spanned<TExpr>>),
@@ -43,6 +43,91 @@ pub enum TBuiltinFnCall {
         string: Spanned<TExpr>,
         idx: Spanned<TExpr>,
     },
+    CharCode {
+        string: Spanned<TExpr>,
+        idx: Spanned<TExpr>,
+    },
+    FromCharCode(Spanned<TExpr>),
+    ListContains {
+        list: Spanned<TExpr>,
+        expr: Spanned<TExpr>,
+    },
+    ListIndexOf {
+        list: Spanned<TExpr>,
+        expr: Spanned<TExpr>,
+    },
+    ListClear(Spanned<TExpr>),
+    ListReverse(Spanned<TExpr>),
+    ListSort(Spanned<TExpr>),
+    Format(Vec<FormatSegment>),
+    Assert {
+        /// The span of the condition (where failures are reported, and which is printed without a message)
+        span: Span,
+        cond: Spanned<TExpr>,
+        message: Option<Spanned<TExpr>>,
+    },
+    AssertEq {
+        span: Span,
+        lhs: Typed<Spanned<TExpr>>,
+        rhs: Typed<Spanned<TExpr>>,
+    },
+    Dbg {
+        /// The span of the argument (which is printed along with it's value)
+        span: Span,
+        expr: Typed<Spanned<TExpr>>,
+    },
+}
+
+/// A segment of a formatted string
+#[derive(Debug)]
+pub enum FormatSegment {
+    /// A literal section of the format string
+    Literal(String),
+    /// An argument that replaces a `{}` placeholder (stringified like `as_str`)
+    Arg(Spanned<TExpr>, Type),
+}
+
+/// The names and signatures of all of the builtin-functions (used for error messages and completions)
+pub const BUILTINS: &[(&str, &str)] = &[
+    ("println", "println!(message: str?) -> nil, or println!(format: str, args..) -> nil"),
+    ("as_str", "as_str!(value: any) -> str"),
+    ("input", "input!(prompt: str) -> str"),
+    ("timer", "timer!() -> num"),
+    ("panic", "panic!(message: str?) -> nil"),
+    ("list_len", "list_len!(list: [T]) -> num"),
+    ("list_get", "list_get!(list: [T], idx: num) -> T"),
+    ("list_push", "list_push!(list: [T], value: T) -> nil"),
+    ("list_insert", "list_insert!(list: [T], idx: num, value: T) -> nil"),
+    ("list_remove", "list_remove!(list: [T], idx: num) -> T"),
+    ("list_replace", "list_replace!(list: [T], idx: num, value: T) -> nil"),
+    ("list_contains", "list_contains!(list: [T], value: T) -> bool"),
+    ("list_index_of", "list_index_of!(list: [T], value: T) -> num"),
+    ("list_clear", "list_clear!(list: [T]) -> nil"),
+    ("list_reverse", "list_reverse!(list: [T]) -> nil"),
+    ("list_sort", "list_sort!(list: [num] | [str]) -> nil"),
+    ("str_len", "str_len!(string: str) -> num"),
+    ("str_get", "str_get!(string: str, idx: num) -> str"),
+    ("char_code", "char_code!(string: str, idx: num) -> num"),
+    ("from_char_code", "from_char_code!(code: num) -> str"),
+    ("format", "format!(format: str, args..) -> str"),
+    ("assert", "assert!(cond: bool, message: str?) -> nil"),
+    ("assert_eq", "assert_eq!(left: T, right: T) -> nil"),
+    ("dbg", "dbg!(value: T) -> T"),
+];
+
+/// The label `dbg!` prints before the value of it's argument, with the location & source code of the argument (like `[main.srpl:3] x * 2`)
+pub fn dbg_label(src_id: &str, src: &str, span: &Span) -> String {
+    let (line, _) = line_column(src, span.start);
+    format!("[{src_id}:{line}] {}", &src[span.clone()])
+}
+
+/// The span of all the source code of an argument
+///
+/// (the span of an expr starts at it's first operation, like the `+` of `1 + 2`, so this spans all of it's nodes instead)
+fn source_span(arg: &Expr) -> Span {
+    let start = arg.asa.iter().map(|node| node.info.span.start).min().unwrap_or(arg.span.start);
+    let end = arg.asa.iter().map(|node| node.info.span.end).max().unwrap_or(arg.span.end);
+    start..end
 }
 
 /// Add type annotations to builtin-function calls
@@ -59,14 +144,27 @@ pub fn wrap_builtin(ident: &str, ident_span: Span, span: Span, args: &[Expr], ty
         "list_insert" => builtin_list_insert(span, args, type_table, func_table, var_table),
         "list_remove" => builtin_list_remove(span, args, type_table, func_table, var_table),
         "list_replace" => builtin_list_replace(span, args, type_table, func_table, var_table),
+        "list_contains" => builtin_list_contains(span, args, type_table, func_table, var_table),
+        "list_index_of" => builtin_list_index_of(span, args, type_table, func_table, var_table),
+        "list_clear" => builtin_list_clear(span, args, type_table, func_table, var_table),
+        "list_reverse" => builtin_list_reverse(span, args, type_table, func_table, var_table),
+        "list_sort" => builtin_list_sort(span, args, type_table, func_table, var_table),
+        "format" => builtin_format(span, args, type_table, func_table, var_table),
         "str_len" => builtin_str_len(span, args, type_table, func_table, var_table),
         "str_get" => builtin_str_get(span, args, type_table, func_table, var_table),
+        "char_code" => builtin_char_code(span, args, type_table, func_table, var_table),
+        "from_char_code" => builtin_from_char_code(span, args, type_table, func_table, var_table),
+        "assert" => builtin_assert(span, args, type_table, func_table, var_table),
+        "assert_eq" => builtin_assert_eq(span, args, type_table, func_table, var_table),
+        "dbg" => builtin_dbg(span, args, type_table, func_table, var_table),
 
         // if the builtin function is not found, then return error
         _ => return Err(Error::BuiltinNotFound {
             ident_span,
             ident: ident.to_string(),
             call_span: span,
+            similar: suggest::similar(ident, BUILTINS.iter().map(|(name, _)| *name)),
+            is_func: func_table.0.contains_key(ident),
         })
     }
 }
@@ -91,9 +189,9 @@ fn builtin_as_str(span: Span, args: &[Expr], type_table: &TypeTable, func_table:
     }
 
     // evaulate the argument and return it as a string
-    let ((arg, _), _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    let ((arg, arg_type), _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
     Ok((
-        TBuiltinFnCall::AsString(arg),
+        TBuiltinFnCall::AsString(arg, arg_type),
         Type::String,
     ))
 }
@@ -142,13 +240,14 @@ fn builtin_input(span: Span, args: &[Expr], type_table: &TypeTable, func_table:
 
 /// Add type annotations to `println` builtin-function calls
 fn builtin_println(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
-    // make sure there's only one or no arguments, otherwise, throw error
+    // if there's more than one argument, then treat them as a format string and its arguments
+    // (a lone string is printed as-is, so braces in it don't need escaping)
     if args.len() > 1 {
-        return Err(Error::BuiltinManyArgs {
-            call_span: span,
-            max: 0..2,
-            arg_span: args[1].span.clone(),
-        });
+        let (call, _) = builtin_format(span.clone(), args, type_table, func_table, var_table)?;
+        return Ok((
+            TBuiltinFnCall::PrintLn(Some((TExpr::BuiltinFnCall(Box::new(call)), span))),
+            Type::Nil,
+        ));
     }
 
     // if there are no arugments, return early
@@ -651,3 +750,531 @@ fn builtin_str_get(span: Span, args: &[Expr], type_table: &TypeTable, func_table
         Type::String,
     ))
 }
+
+/// Add type annotations to `char_code` builtin-function calls
+fn builtin_char_code(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least two arguments
+    if args.len() < 2 {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 2..3,
+        });
+    }
+
+    // make sure there's only two arguments
+    if args.len() > 2 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 2..3,
+            arg_span: args[2].span.clone(),
+        });
+    }
+
+    // wrap the str expr and make sure it's of type string
+    let (str_expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    if str_expr.1 != Type::String {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: str_expr.0.1,
+            param_type: Type::String,
+            arg_type: str_expr.1,
+            call_span: span,
+        });
+    }
+
+    // wrap the index expr and make sure it's of type number
+    let (idx_expr, _) = wrap_expr(&args[1].asa, type_table, func_table, var_table)?;
+    if idx_expr.1 != Type::Number {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: idx_expr.0.1,
+            param_type: Type::Number,
+            arg_type: idx_expr.1,
+            call_span: span,
+        });
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::CharCode {
+            string: str_expr.0,
+            idx: idx_expr.0,
+        },
+        Type::Number,
+    ))
+}
+
+/// Add type annotations to `from_char_code` builtin-function calls
+fn builtin_from_char_code(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..2,
+        });
+    }
+
+    // make sure there's only one argument
+    if args.len() > 1 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 1..2,
+            arg_span: args[1].span.clone(),
+        });
+    }
+
+    // wrap the expr and make sure it's of type num
+    let (expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    if expr.1 != Type::Number {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: expr.0.1,
+            param_type: Type::Number,
+            arg_type: expr.1,
+            call_span: span,
+        });
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::FromCharCode(expr.0),
+        Type::String,
+    ))
+}
+
+/// Add type annotations to `list_contains` builtin-function calls
+fn builtin_list_contains(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least two arguments
+    if args.len() < 2 {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 2..3,
+        });
+    }
+
+    // make sure there's only two arguments
+    if args.len() > 2 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 2..3,
+            arg_span: args[2].span.clone(),
+        });
+    }
+
+    // wrap the list expr
+    let (list_expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+
+    // get the list type
+    let Type::List(list_type) = list_expr.1
+    else {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: list_expr.0.1,
+            param_type: Type::List(Box::new(list_expr.1.clone())),
+            arg_type: list_expr.1,
+            call_span: span,
+        });
+    };
+
+    // wrap the element expr and make sure it's of the right type
+    let (expr, _) = wrap_expr(&args[1].asa, type_table, func_table, var_table)?;
+    if expr.1 != *list_type {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: expr.0.1,
+            param_type: *list_type,
+            arg_type: expr.1,
+            call_span: span,
+        });
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::ListContains {
+            list: list_expr.0,
+            expr: expr.0,
+        },
+        Type::Bool,
+    ))
+}
+
+/// Add type annotations to `list_index_of` builtin-function calls
+fn builtin_list_index_of(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least two arguments
+    if args.len() < 2 {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 2..3,
+        });
+    }
+
+    // make sure there's only two arguments
+    if args.len() > 2 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 2..3,
+            arg_span: args[2].span.clone(),
+        });
+    }
+
+    // wrap the list expr
+    let (list_expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+
+    // get the list type
+    let Type::List(list_type) = list_expr.1
+    else {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: list_expr.0.1,
+            param_type: Type::List(Box::new(list_expr.1.clone())),
+            arg_type: list_expr.1,
+            call_span: span,
+        });
+    };
+
+    // wrap the element expr and make sure it's of the right type
+    let (expr, _) = wrap_expr(&args[1].asa, type_table, func_table, var_table)?;
+    if expr.1 != *list_type {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: expr.0.1,
+            param_type: *list_type,
+            arg_type: expr.1,
+            call_span: span,
+        });
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::ListIndexOf {
+            list: list_expr.0,
+            expr: expr.0,
+        },
+        Type::Number,
+    ))
+}
+
+/// Add type annotations to `list_clear` builtin-function calls
+fn builtin_list_clear(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..2,
+        });
+    }
+
+    // make sure there's only one argument
+    if args.len() > 1 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 1..2,
+            arg_span: args[1].span.clone(),
+        });
+    }
+
+    // wrap the expr and make sure it's of type list
+    let (expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    match expr.1 {
+        Type::List(_) => (),
+        _ => return Err(Error::BuiltinArgTypeMismatch {
+            span: expr.0.1,
+            param_type: Type::List(Box::new(expr.1.clone())),
+            arg_type: expr.1,
+            call_span: span,
+        }),
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::ListClear(expr.0),
+        Type::Nil,
+    ))
+}
+
+/// Add type annotations to `list_reverse` builtin-function calls
+fn builtin_list_reverse(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..2,
+        });
+    }
+
+    // make sure there's only one argument
+    if args.len() > 1 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 1..2,
+            arg_span: args[1].span.clone(),
+        });
+    }
+
+    // wrap the expr and make sure it's of type list
+    let (expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    match expr.1 {
+        Type::List(_) => (),
+        _ => return Err(Error::BuiltinArgTypeMismatch {
+            span: expr.0.1,
+            param_type: Type::List(Box::new(expr.1.clone())),
+            arg_type: expr.1,
+            call_span: span,
+        }),
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::ListReverse(expr.0),
+        Type::Nil,
+    ))
+}
+
+/// Add type annotations to `list_sort` builtin-function calls
+///
+/// (strings are ordered like scratch compares them: case-insensitively, and numerically if both look like numbers, so `"10"` is after `"9"`)
+fn builtin_list_sort(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..2,
+        });
+    }
+
+    // make sure there's only one argument
+    if args.len() > 1 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 1..2,
+            arg_span: args[1].span.clone(),
+        });
+    }
+
+    // wrap the expr and make sure it's a list of either numbers or strings (the only orderable types)
+    let (expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    match expr.1 {
+        Type::List(ref list_type) if **list_type == Type::Number || **list_type == Type::String => (),
+        _ => return Err(Error::UnsortableList {
+            span: expr.0.1,
+            arg_type: expr.1,
+            call_span: span,
+        }),
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::ListSort(expr.0),
+        Type::Nil,
+    ))
+}
+
+/// Add type annotations to `format` builtin-function calls
+fn builtin_format(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..2,
+        });
+    }
+
+    // make sure the format string is a string literal (so it can be checked at compile-time)
+    let fmt_span = args[0].span.clone();
+    let fmt = match &args[0].asa[..] {
+        [Node { oper: ExprOper::String(fmt), .. }] => fmt,
+        _ => return Err(Error::FormatNotLiteral {
+            span: source_span(&args[0]),
+            call_span: span,
+        }),
+    };
+
+    // parse the format string and make sure there's the right amount of arguments for it's placeholders
+    let pieces = parse_format(fmt, fmt_span.clone(), span.clone())?;
+    let placeholders = pieces.iter().filter(|piece| piece.is_none()).count();
+    if placeholders != args.len() - 1 {
+        return Err(Error::FormatArgsAmount {
+            fmt_span,
+            call_span: span,
+            placeholders,
+            given_amount: args.len() - 1,
+        });
+    }
+
+    // wrap the arguments in types and place them into the segments
+    let mut args = args[1..].iter();
+    let mut segments = Vec::new();
+    for piece in pieces {
+        match piece {
+            Some(literal) => segments.push(FormatSegment::Literal(literal)),
+            None => {
+                let arg = args.next().unwrap(); // already checked the amount of args
+                let ((arg, arg_type), _) = wrap_expr(&arg.asa, type_table, func_table, var_table)?;
+                segments.push(FormatSegment::Arg(arg, arg_type));
+            },
+        }
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::Format(segments),
+        Type::String,
+    ))
+}
+
+/// Parses a format string into it's literal pieces and `{}` placeholders (`None`)
+///
+/// (braces can be escaped by doubling them, like `{{` and `}}`)
+fn parse_format(fmt: &str, span: Span, call_span: Span) -> Result<Vec<Option<String>>, Error> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                literal.push(c);
+                chars.next();
+            },
+            ('{', Some('}')) => {
+                chars.next();
+                if !literal.is_empty() {
+                    pieces.push(Some(std::mem::take(&mut literal)));
+                }
+                pieces.push(None);
+            },
+            ('{', _) | ('}', _) => return Err(Error::FormatUnmatchedBrace { span, call_span }),
+            _ => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        pieces.push(Some(literal));
+    }
+
+    Ok(pieces)
+}
+
+/// Add type annotations to `assert` builtin-function calls
+fn builtin_assert(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..3,
+        });
+    }
+
+    // make sure there's only one or two arguments
+    if args.len() > 2 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 1..3,
+            arg_span: args[2].span.clone(),
+        });
+    }
+
+    // wrap the condition and make sure it's a boolean
+    let (cond, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    if cond.1 != Type::Bool {
+        return Err(Error::BuiltinWrongType {
+            call_span: span,
+            expected: Type::Bool,
+            arg_type: cond.1,
+            arg_span: cond.0.1,
+        });
+    }
+
+    // wrap the message (if there is one) and make sure it's a string
+    let message = match args.get(1) {
+        Some(arg) => {
+            let (message, _) = wrap_expr(&arg.asa, type_table, func_table, var_table)?;
+            if message.1 != Type::String {
+                return Err(Error::BuiltinWrongType {
+                    call_span: span,
+                    expected: Type::String,
+                    arg_type: message.1,
+                    arg_span: message.0.1,
+                });
+            }
+            Some(message.0)
+        },
+        None => None,
+    };
+
+    // return completed builtin-fn call (failures are reported at the condition)
+    Ok((
+        TBuiltinFnCall::Assert {
+            span: source_span(&args[0]),
+            cond: cond.0,
+            message,
+        },
+        Type::Nil,
+    ))
+}
+
+/// Add type annotations to `assert_eq` builtin-function calls
+fn builtin_assert_eq(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least two arguments
+    if args.len() < 2 {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 2..3,
+        });
+    }
+
+    // make sure there's only two arguments
+    if args.len() > 2 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 2..3,
+            arg_span: args[2].span.clone(),
+        });
+    }
+
+    // wrap both sides and make sure they're of the same type
+    let (lhs, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    let (rhs, _) = wrap_expr(&args[1].asa, type_table, func_table, var_table)?;
+    if lhs.1 != rhs.1 {
+        return Err(Error::BuiltinArgTypeMismatch {
+            span: rhs.0.1,
+            param_type: lhs.1,
+            arg_type: rhs.1,
+            call_span: span,
+        });
+    }
+
+    // return completed builtin-fn call
+    Ok((
+        TBuiltinFnCall::AssertEq {
+            span,
+            lhs,
+            rhs,
+        },
+        Type::Nil,
+    ))
+}
+
+/// Add type annotations to `dbg` builtin-function calls
+fn builtin_dbg(span: Span, args: &[Expr], type_table: &TypeTable, func_table: &FuncTable, var_table: &mut VarTable) -> Result<Typed<TBuiltinFnCall>, Error> {
+    // make sure there's at least one argument
+    if args.is_empty() {
+        return Err(Error::BuiltinLittleArgs {
+            call_span: span,
+            min: 1..2,
+        });
+    }
+
+    // make sure there's only one argument
+    if args.len() > 1 {
+        return Err(Error::BuiltinManyArgs {
+            call_span: span,
+            max: 1..2,
+            arg_span: args[1].span.clone(),
+        });
+    }
+
+    // the value is returned unchanged, so it's of the same type as the argument
+    let (expr, _) = wrap_expr(&args[0].asa, type_table, func_table, var_table)?;
+    let expr_type = expr.1.clone();
+    Ok((
+        TBuiltinFnCall::Dbg {
+            span: source_span(&args[0]),
+            expr,
+        },
+        expr_type,
+    ))
+}
@@ -126,15 +126,21 @@ pub fn wrap_expr(asa: &[Node<ExprOper>], type_table: &TypeTable, func_table: &Fu
                 // push it
                 exprs.push((expr, expr_span));
             }
+
+            // scratch lists can only hold numbers & strings (so not other lists)
+            let list_type = Type::List(Box::new(list_type));
+            if list_type.has_nested_list() {
+                return Err(Error::NestedList { span: asa[0].info.span.clone(), list_type });
+            }
             
             // return list definition
             (
                 (
                     (
-                        TExpr::List(list_type.clone(), exprs),
+                        TExpr::List(match &list_type { Type::List(el_type) => (**el_type).clone(), _ => unreachable!() }, exprs),
                         asa[0].info.span.clone(),
                     ),
-                    Type::List(Box::new(list_type))
+                    list_type
                 ),
                 0,
             )
@@ -493,7 +499,7 @@ pub fn wrap_expr(asa: &[Node<ExprOper>], type_table: &TypeTable, func_table: &Fu
             // try get the type of the variable from the var-table, otherwise throw error
             let (ident, var_type) = match var_table.get(ident) {
                 Some((ident, entry)) => (ident, entry.var_type.clone()),
-                None => return Err(Error::VarNotFound { span: asa[0].info.span.clone() })
+                None => return Err(Error::VarNotFound { span: asa[0].info.span.clone(), similar: var_table.similar(ident) })
             };
 
             (
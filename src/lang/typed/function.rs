@@ -1,6 +1,6 @@
 use ketchup::Span;
 use crate::lang::{error::typed::Error, parser::expr::Expr, typed::expr, Spanned};
-use super::{expr::TExpr, symbol_table::{FuncTable, TypeTable, VarTable}, types::{Type, Typed}};
+use super::{builtin::BUILTINS, expr::TExpr, suggest, symbol_table::{FuncTable, TypeTable, VarTable}, types::{Type, Typed}};
 
 /// A function type signature
 #[derive(Debug, Clone)]
@@ -26,6 +26,8 @@ pub fn wrap_call(
             ident_span,
             ident: ident.to_string(),
             call_span: span,
+            similar: suggest::similar(ident, func_table.0.keys().map(String::as_str)),
+            is_builtin: BUILTINS.iter().any(|(name, _)| *name == ident),
         });
     };
 
@@ -54,8 +56,8 @@ pub fn wrap_call(
                 func_span: signature.1.clone(),
                 call_span: span.clone(),
                 arg_span: arg.span.clone(),
-                arg_type: wrapped.1,
-                param_type: param.0.1.clone(),
+                arg_type: Box::new(wrapped.1),
+                param_type: Box::new(param.0.1.clone()),
             })
         }
 
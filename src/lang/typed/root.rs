@@ -1,6 +1,6 @@
 use std::collections::HashMap;
-use crate::lang::{error::typed::Error, parser::root::Roots};
-use super::{block::{self, TBlock}, function::FuncSignature, symbol_table::{FuncTable, TypeTable, VarTable, VarTableEntry}};
+use crate::lang::{error::typed::Error, parser::{function::FuncDef, root::Roots}, Spanned};
+use super::{block::{self, TBlock}, function::FuncSignature, types::{self, Type}, symbol_table::{FuncTable, TypeTable, VarTable, VarTableEntry}};
 
 /// A type annotated representation of the entire project with all the roots evaluated statically
 #[derive(Debug)]
@@ -10,6 +10,9 @@ pub struct Project {
     
     /// Additional user-defined procedures
     pub procedures: Vec<(String, TBlock)>,
+
+    /// The test functions (which are never compiled, only run by `scrapile test`)
+    pub tests: Vec<(String, TBlock)>,
 }
 
 /// Wraps the root of the project in types and returns a single, safe and valid project root
@@ -18,21 +21,31 @@ pub fn wrap_root(roots: &Roots) -> Result<Project, Error> {
     let mut func_table = FuncTable(HashMap::new());
 
     // iterate through the functions and gather their signatures
+    let mut test_spans = HashMap::new();
     for func in roots.funcs.iter() {
-        let signature = (FuncSignature {
-            params: func.0.params.iter()
-                .map(|((ident, ptype), span)| ((ident.clone(), ptype.clone()), span.clone()))
-                .collect::<Vec<_>>(),
-            retrn_type: func.0.retrn_type.clone(),
-        }, func.1.clone());
-
-        // insert and also check for duplicate function definitions
-        if let Some(old) = func_table.0.insert(func.0.ident.clone(), signature) {
+        // tests can't be called, so they're only checked for duplicates (tests & functions share names)
+        let first_span = func_table.0.get(&func.0.ident).map(|(_, span)| span).or(test_spans.get(&func.0.ident));
+        if let Some(first_span) = first_span {
             return Err(Error::MultipleFunc {
-                first_span: old.1,
+                first_span: first_span.clone(),
                 additional_span: func.1.clone(),
-            })
+            });
+        }
+
+        if func.0.test {
+            // tests are run without any arguments and their results are ignored
+            if let Some((_, span)) = func.0.params.first() {
+                return Err(Error::InvalidTest { span: span.clone(), test_span: func.1.clone() });
+            }
+            if func.0.retrn_type.0 != Type::Nil {
+                return Err(Error::InvalidTest { span: func.0.retrn_type.1.clone(), test_span: func.1.clone() });
+            }
+
+            test_spans.insert(func.0.ident.clone(), func.1.clone());
+            continue;
         }
+
+        func_table.0.insert(func.0.ident.clone(), signature(func));
     }
 
     // make sure there's one and only one main root, otherwise throw an error
@@ -57,37 +70,74 @@ pub fn wrap_root(roots: &Roots) -> Result<Project, Error> {
 
     // wrap the rest of the function definitions in types
     let mut procedures = Vec::new();
+    let mut tests = Vec::new();
     for func in roots.funcs.iter() {
-        let mut var_table = VarTable::new(format!("$func${}", func.0.ident));
-
-        // insert the parameters
-        for param in func.0.params.iter() {
-            var_table.insert(param.0.0.clone(), VarTableEntry {
-                var_type: param.0.1.clone(),
-                mutable: false,
-                span: param.1.clone(),
-            });
-        }
-
-        // wrap the procedures
-        let wrapped = block::wrap_block(func.0.body.0.clone(), &type_table, &func_table, var_table)?;
-
-        // make sure the body's return value is of the right type
-        if wrapped.1 != func.0.retrn_type.0 {
-            return Err(Error::RetrnTypeMismatch {
-                span: wrapped.0.tail.map(|((_, span), _)| span.clone()).unwrap_or_else(|| func.0.body.1.clone()),
-                type_span: func.0.retrn_type.1.clone(),
-                expr_type: wrapped.1.clone(),
-                retrn_type: func.0.retrn_type.0.clone(),
-            })
+        if func.0.test {
+            let wrapped = block::wrap_block(func.0.body.0.clone(), &type_table, &func_table, VarTable::new(format!("$test${}", func.0.ident)))?;
+            if wrapped.1 != Type::Nil {
+                return Err(Error::RetrnTypeMismatch {
+                    span: wrapped.0.tail.map(|((_, span), _)| span.clone()).unwrap_or_else(|| func.0.body.1.clone()),
+                    type_span: func.0.retrn_type.1.clone(),
+                    expr_type: wrapped.1.clone(),
+                    retrn_type: Type::Nil,
+                })
+            }
+            tests.push((func.0.ident.clone(), wrapped.0));
+            continue;
         }
 
-        // push the wrapped block
-        procedures.push((func.0.ident.clone(), wrapped.0));
+        // wrap & push the procedures
+        procedures.push((func.0.ident.clone(), wrap_func(&func.0, &type_table, &func_table)?));
     }
 
     Ok(Project {
         main,
         procedures,
+        tests,
     })
 }
+
+/// The signature of a function definition (for the func-table)
+pub fn signature(func: &Spanned<FuncDef>) -> Spanned<FuncSignature> {
+    (FuncSignature {
+        params: func.0.params.iter()
+            .map(|((ident, ptype), span)| ((ident.clone(), ptype.clone()), span.clone()))
+            .collect::<Vec<_>>(),
+        retrn_type: func.0.retrn_type.clone(),
+    }, func.1.clone())
+}
+
+/// Wraps the body of a (non-test) function in types, given that it's signature is already in the func-table
+pub fn wrap_func(func: &FuncDef, type_table: &TypeTable, func_table: &FuncTable) -> Result<TBlock, Error> {
+    let mut var_table = VarTable::new(format!("$func${}", func.ident));
+
+    // verify the types of the signature
+    for ((_, ptype), span) in func.params.iter() {
+        types::verify((ptype.clone(), span.clone()), type_table)?;
+    }
+    types::verify(func.retrn_type.clone(), type_table)?;
+
+    // insert the parameters
+    for param in func.params.iter() {
+        var_table.insert(param.0.0.clone(), VarTableEntry {
+            var_type: param.0.1.clone(),
+            mutable: false,
+            span: param.1.clone(),
+        });
+    }
+
+    // wrap the procedure
+    let wrapped = block::wrap_block(func.body.0.clone(), type_table, func_table, var_table)?;
+
+    // make sure the body's return value is of the right type
+    if wrapped.1 != func.retrn_type.0 {
+        return Err(Error::RetrnTypeMismatch {
+            span: wrapped.0.tail.map(|((_, span), _)| span.clone()).unwrap_or_else(|| func.body.1.clone()),
+            type_span: func.retrn_type.1.clone(),
+            expr_type: wrapped.1.clone(),
+            retrn_type: func.retrn_type.0.clone(),
+        })
+    }
+
+    Ok(wrapped.0)
+}
@@ -10,6 +10,7 @@ pub enum TStmt {
     /// A variable declaration with `let`
     VarDeclare {
         ident: String,
+        mutable: bool,
         value: Typed<Spanned<TExpr>>,
     },
 
@@ -49,7 +50,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             // make sure the variable exists and if so, get the type of it
             let (var_ident, var_type, mutable, var_span) = match var_table.get(&ident.0) {
                 Some((ident, entry)) => (ident, entry.var_type.clone(), entry.mutable, entry.span.clone()),
-                None => return Err(Error::VarNotFound { span: ident.1 }),
+                None => return Err(Error::VarNotFound { span: ident.1, similar: var_table.similar(&ident.0) }),
             };
 
             // make sure the variable is mutable in the first place
@@ -74,7 +75,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             // make sure the variable exists and if so, get the type of it
             let (var_ident, var_type, mutable, var_span) = match var_table.get(&ident.0) {
                 Some((ident, entry)) => (ident, entry.var_type.clone(), entry.mutable, entry.span.clone()),
-                None => return Err(Error::VarNotFound { span: ident.1 }),
+                None => return Err(Error::VarNotFound { span: ident.1, similar: var_table.similar(&ident.0) }),
             };
 
             // make sure the variable is mutable in the first place
@@ -102,7 +103,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             // make sure the variable exists and if so, get the type of it
             let (var_ident, var_type, mutable, var_span) = match var_table.get(&ident.0) {
                 Some((ident, entry)) => (ident, entry.var_type.clone(), entry.mutable, entry.span.clone()),
-                None => return Err(Error::VarNotFound { span: ident.1 }),
+                None => return Err(Error::VarNotFound { span: ident.1, similar: var_table.similar(&ident.0) }),
             };
 
             // make sure the variable is mutable in the first place
@@ -130,7 +131,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             // make sure the variable exists and if so, get the type of it
             let (var_ident, var_type, mutable, var_span) = match var_table.get(&ident.0) {
                 Some((ident, entry)) => (ident, entry.var_type.clone(), entry.mutable, entry.span.clone()),
-                None => return Err(Error::VarNotFound { span: ident.1 }),
+                None => return Err(Error::VarNotFound { span: ident.1, similar: var_table.similar(&ident.0) }),
             };
 
             // make sure the variable is mutable in the first place
@@ -158,7 +159,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             // make sure the variable exists and if so, get the type of it
             let (var_ident, var_type, mutable, var_span) = match var_table.get(&ident.0) {
                 Some((ident, entry)) => (ident, entry.var_type.clone(), entry.mutable, entry.span.clone()),
-                None => return Err(Error::VarNotFound { span: ident.1 }),
+                None => return Err(Error::VarNotFound { span: ident.1, similar: var_table.similar(&ident.0) }),
             };
 
             // make sure the variable is mutable in the first place
@@ -186,7 +187,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             // make sure the variable exists and if so, get the type of it
             let (var_ident, var_type, mutable, var_span) = match var_table.get(&ident.0) {
                 Some((ident, entry)) => (ident, entry.var_type.clone(), entry.mutable, entry.span.clone()),
-                None => return Err(Error::VarNotFound { span: ident.1 }),
+                None => return Err(Error::VarNotFound { span: ident.1, similar: var_table.similar(&ident.0) }),
             };
 
             // make sure the variable is mutable in the first place
@@ -228,7 +229,7 @@ pub fn wrap_stmt(stmt: Spanned<Stmt>, type_table: &TypeTable, func_table: &FuncT
             
             // return completed variable declaration
             Ok((
-                TStmt::VarDeclare { ident: var_table.get_ident(&ident), value },
+                TStmt::VarDeclare { ident: var_table.get_ident(&ident), mutable, value },
                 Type::Nil,
             ))
         },
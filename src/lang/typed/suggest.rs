@@ -0,0 +1,40 @@
+//! "did you mean" suggestions for identifiers that couldn't be found
+
+/// The edit distance between two strings, in chars (insertions, deletions, substitutions & swapping adjacent chars)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+
+    // the distances between the prefixes of `a` and `b`
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    (0..=a.len()).for_each(|i| table[i][0] = i);
+    (0..=b.len()).for_each(|j| table[0][j] = j);
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            table[i][j] = (table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1)
+                .min(table[i - 1][j - 1] + cost);
+
+            // swapped adjacent chars (like `cuont` for `count`)
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                table[i][j] = table[i][j].min(table[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    table[a.len()][b.len()]
+}
+
+/// Finds the candidate that's most similar to an identifier (if any of them are similar enough to be a likely typo)
+pub fn similar<'a>(ident: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    // allow roughly one typo for every three characters
+    let max_distance = (ident.chars().count() / 3).max(1);
+
+    candidates.into_iter()
+        .filter(|candidate| *candidate != ident)
+        .map(|candidate| (edit_distance(ident, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min() // ties are broken alphabetically, so suggestions are deterministic
+        .map(|(_, candidate)| candidate.to_string())
+}
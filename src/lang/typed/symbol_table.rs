@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use ketchup::Span;
 use crate::lang::Spanned;
-use super::{function::FuncSignature, types::Type};
+use super::{function::FuncSignature, suggest, types::Type};
 
 /// A hashmap of all the types in a project, the key is the identifier of the type, each type is another hashmap of string property identifiers corresponding to a `u32` unique identifier and a type for that property
 #[derive(Debug)]
@@ -66,6 +66,12 @@ impl VarTable {
         format!("{}/{ident}", self.prefix)
     }
 
+    /// The prefix given to the identifiers of the variables in this scope
+    #[inline]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
     /// Gets the a variable entry and it's identifier from either this scope or it's parent scope
     pub fn get(&self, key: &str) -> Option<(String, &VarTableEntry)> {
         match self.table.get(key) {
@@ -79,6 +85,18 @@ impl VarTable {
         }
     }
 
+    /// Finds a similarly named variable in this scope or it's parent scopes (for "did you mean" suggestions)
+    pub fn similar(&self, key: &str) -> Option<String> {
+        let mut names = Vec::new();
+        let mut scope = Some(self);
+        while let Some(table) = scope {
+            names.extend(table.table.keys().map(String::as_str));
+            scope = table.parent.as_deref();
+        }
+
+        suggest::similar(key, names)
+    }
+
     /// Inserts a variable entry into the current scope
     #[inline]
     pub fn insert(&mut self, key: String, entry: VarTableEntry) {
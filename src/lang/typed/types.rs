@@ -1,6 +1,6 @@
 use std::fmt::Display;
 use crate::lang::{error::typed::Error, Spanned};
-use super::symbol_table::TypeTable;
+use super::{suggest, symbol_table::TypeTable};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
@@ -31,17 +31,33 @@ impl Display for Type {
     }
 }
 
-/// Verifies if a type exists or not and returns it again
+/// Verifies if a type exists (and isn't a list of lists) or not and returns it again
 pub fn verify(atype: Spanned<Type>, type_table: &TypeTable) -> Result<Type, Error> {
+    if atype.0.has_nested_list() {
+        return Err(Error::NestedList { span: atype.1, list_type: atype.0 });
+    }
+
     // only need to check the validity of custom types
     if let (Type::Custom { ref ident }, span) = atype {
         // make sure it exists in the type table, otherwise throw error
         if type_table.0.get(ident).is_none() {
-            return Err(Error::TypeNotFound { span });
+            let primitives = ["num", "str", "bool", "nil"].into_iter();
+            return Err(Error::TypeNotFound { span, similar: suggest::similar(ident, primitives.chain(type_table.0.keys().map(String::as_str))) });
         }
     }
 
     Ok(atype.0)
 }
 
+impl Type {
+    /// If the type is (or contains) a list of lists
+    pub fn has_nested_list(&self) -> bool {
+        match self {
+            Type::List(el_type) => matches!(**el_type, Type::List(_)) || el_type.has_nested_list(),
+            Type::Tuple(types) => types.iter().any(Type::has_nested_list),
+            _ => false,
+        }
+    }
+}
+
 pub type Typed<T> = (T, Type);
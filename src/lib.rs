@@ -1,3 +1,8 @@
 pub mod scratch;
 pub mod lang;
 pub mod cli;
+pub mod manifest;
+pub mod compile;
+pub mod lsp;
+
+pub use compile::{compile, CompileOptions, CompiledProject, Diagnostics};
@@ -0,0 +1,303 @@
+//! A language server (over stdio) for source files, built on top of `lang::analysis`
+//!
+//! supports publishing diagnostics, hover types, go-to-definition & completions of builtins and functions
+
+use std::{collections::HashMap, io::{self, BufRead, Write}};
+use color_eyre::eyre::{self, eyre, Context};
+use json::{object, JsonValue};
+use crate::{lang::{analysis::{self, Analysis}, edition::Edition, error::Severity, typed::builtin::BUILTINS}, manifest::Manifest};
+
+/// An opened source file
+struct Document {
+    src: String,
+    edition: Edition,
+    analysis: Analysis,
+}
+
+/// The state of the language server
+struct Server {
+    /// The edition given on the cli (overrides the project manifests)
+    cli_edition: Option<Edition>,
+    /// The opened source files by their uri
+    documents: HashMap<String, Document>,
+    /// If the client has requested a shutdown
+    shutdown: bool,
+}
+
+/// Runs the language server until the client exits, returning if the client requested a shutdown beforehand
+///
+/// (exiting without a shutdown request is an error according to the spec, so the process should then exit with a non-zero status)
+pub fn run(cli_edition: Option<Edition>) -> eyre::Result<bool> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut server = Server { cli_edition, documents: HashMap::new(), shutdown: false };
+
+    while let Some(message) = read_message(&mut stdin)? {
+        let method = message["method"].as_str().unwrap_or_default().to_string();
+        let params = &message["params"];
+
+        // notifications don't have ids and don't get responses
+        if message["id"].is_null() {
+            match method.as_str() {
+                "exit" => break,
+                "textDocument/didOpen" => {
+                    let doc = &params["textDocument"];
+                    let uri = doc["uri"].as_str().unwrap_or_default();
+                    server.update(uri, doc["text"].as_str().unwrap_or_default().to_string());
+                    write_message(&mut stdout, &server.diagnostics(uri))?;
+                },
+                "textDocument/didChange" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    // only full syncs are supported, so the last change is the entire document
+                    if let Some(text) = params["contentChanges"].members().last().and_then(|change| change["text"].as_str()) {
+                        server.update(uri, text.to_string());
+                        write_message(&mut stdout, &server.diagnostics(uri))?;
+                    }
+                },
+                "textDocument/didClose" => {
+                    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                    server.documents.remove(uri);
+                    write_message(&mut stdout, &object! {
+                        jsonrpc: "2.0",
+                        method: "textDocument/publishDiagnostics",
+                        params: { uri: uri, diagnostics: [] },
+                    })?;
+                },
+                _ => (), // includes `initialized`
+            }
+            continue;
+        }
+
+        let result = match method.as_str() {
+            "initialize" => Ok(object! {
+                capabilities: {
+                    textDocumentSync: 1,
+                    hoverProvider: true,
+                    definitionProvider: true,
+                    completionProvider: { triggerCharacters: [] },
+                },
+                serverInfo: { name: "scrapile", version: env!("CARGO_PKG_VERSION") },
+            }),
+            "shutdown" => {
+                server.shutdown = true;
+                Ok(JsonValue::Null)
+            },
+            "textDocument/hover" => Ok(server.with_document(params, Server::hover)),
+            "textDocument/definition" => Ok(server.with_document(params, Server::definition)),
+            "textDocument/completion" => Ok(server.completion(params)),
+            _ => Err((-32601, format!("unknown method `{method}`"))),
+        };
+
+        let mut response = object! { jsonrpc: "2.0", id: message["id"].clone() };
+        match result {
+            Ok(result) => response["result"] = result,
+            Err((code, message)) => response["error"] = object! { code: code, message: message },
+        }
+        write_message(&mut stdout, &response)?;
+    }
+
+    Ok(server.shutdown)
+}
+
+impl Server {
+    /// Re-analyses a source file after it's been opened or changed
+    fn update(&mut self, uri: &str, src: String) {
+        let edition = self.documents.get(uri)
+            .map(|doc| doc.edition)
+            .unwrap_or_else(|| self.find_edition(uri));
+        let analysis = analysis::analyse(&src, edition);
+
+        self.documents.insert(uri.to_string(), Document { src, edition, analysis });
+    }
+
+    /// Determines the edition of a source file (the cli takes priority over the project manifest)
+    fn find_edition(&self, uri: &str) -> Edition {
+        let manifest = uri_to_path(uri).and_then(|path| Manifest::find(path).ok().flatten());
+        self.cli_edition
+            .or(manifest.and_then(|(_, manifest)| manifest.edition))
+            .unwrap_or_default()
+    }
+
+    /// The `publishDiagnostics` notification of a source file
+    fn diagnostics(&self, uri: &str) -> JsonValue {
+        let doc = &self.documents[uri];
+        let diagnostics = doc.analysis.diagnostics.iter().map(|diagnostic| {
+            let mut message = diagnostic.message.clone();
+            for label in diagnostic.labels.iter().filter(|label| !label.message.is_empty() && label.message != diagnostic.message) {
+                message.push_str(&format!("\n{}", label.message));
+            }
+            if let Some(help) = &diagnostic.help {
+                message.push_str(&format!("\nhelp: {help}"));
+            }
+            for note in diagnostic.notes.iter() {
+                message.push_str(&format!("\nnote: {note}"));
+            }
+
+            object! {
+                range: range(&doc.src, diagnostic.primary_span()),
+                severity: match diagnostic.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                },
+                code: diagnostic.code,
+                source: "scrapile",
+                message: message,
+            }
+        }).collect::<Vec<_>>();
+
+        object! {
+            jsonrpc: "2.0",
+            method: "textDocument/publishDiagnostics",
+            params: { uri: uri, diagnostics: diagnostics },
+        }
+    }
+
+    /// Runs a request on the document & byte offset of a `TextDocumentPositionParams` (or returns null if the document isn't open)
+    fn with_document(&self, params: &JsonValue, f: fn(&Document, &str, usize) -> JsonValue) -> JsonValue {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match self.documents.get(uri) {
+            Some(doc) => f(doc, uri, position_to_offset(&doc.src, &params["position"])),
+            None => JsonValue::Null,
+        }
+    }
+
+    fn hover(doc: &Document, _: &str, offset: usize) -> JsonValue {
+        let contents = if let Some(func) = doc.analysis.func_at(offset) {
+            let mut contents = format!("```scrapile\n{}\n```", func.signature);
+            if !func.docs.is_empty() {
+                contents.push_str(&format!("\n\n{}", func.docs.join("\n")));
+            }
+            contents
+        } else if let Some((hover_type, _)) = doc.analysis.type_at(offset) {
+            format!("```scrapile\n{hover_type}\n```")
+        } else {
+            return JsonValue::Null;
+        };
+
+        object! { contents: { kind: "markdown", value: contents } }
+    }
+
+    fn definition(doc: &Document, uri: &str, offset: usize) -> JsonValue {
+        match doc.analysis.definition_at(offset) {
+            Some(span) => object! { uri: uri, range: range(&doc.src, span.clone()) },
+            None => JsonValue::Null,
+        }
+    }
+
+    fn completion(&self, params: &JsonValue) -> JsonValue {
+        const FUNCTION_KIND: u8 = 3;
+
+        let mut items = BUILTINS.iter()
+            .map(|(name, signature)| object! {
+                label: format!("{name}!"),
+                kind: FUNCTION_KIND,
+                detail: *signature,
+            })
+            .collect::<Vec<_>>();
+
+        // user-defined functions of the document
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        if let Some(doc) = self.documents.get(uri) {
+            items.extend(doc.analysis.funcs.iter().map(|func| object! {
+                label: func.ident.as_str(),
+                kind: FUNCTION_KIND,
+                detail: func.signature.as_str(),
+                documentation: func.docs.join("\n"),
+            }));
+        }
+
+        JsonValue::Array(items)
+    }
+}
+
+/// Reads a single json-rpc message (returns `None` once stdin is closed)
+fn read_message(reader: &mut impl BufRead) -> eyre::Result<Option<JsonValue>> {
+    let mut content_length = None;
+
+    // read the headers
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).wrap_err("while reading a language server message header")? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() { break }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse::<usize>().wrap_err("while parsing the `Content-Length` header")?);
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| eyre!("language server message is missing a `Content-Length` header"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content).wrap_err("while reading a language server message")?;
+
+    let content = String::from_utf8(content).wrap_err("language server message isn't valid utf-8")?;
+    Ok(Some(json::parse(&content).wrap_err("while parsing a language server message")?))
+}
+
+/// Writes a single json-rpc message
+fn write_message(writer: &mut impl Write, message: &JsonValue) -> eyre::Result<()> {
+    let content = message.dump();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush().wrap_err("while writing a language server message")
+}
+
+/// Converts a `file://` uri into a file path
+pub fn uri_to_path(uri: &str) -> Option<String> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::new();
+    let mut chars = path.bytes();
+
+    // decode percent-encoded characters
+    while let Some(byte) = chars.next() {
+        if byte == b'%' {
+            let hex = [chars.next()?, chars.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Converts a lsp position (a line & a utf-16 character offset) into a byte offset
+pub fn position_to_offset(src: &str, position: &JsonValue) -> usize {
+    let line = position["line"].as_usize().unwrap_or_default();
+    let character = position["character"].as_usize().unwrap_or_default();
+
+    let Some(line_start) = (0..line).try_fold(0, |start, _| src[start..].find('\n').map(|i| start + i + 1)) else {
+        return src.len();
+    };
+
+    let mut utf16 = 0;
+    for (i, c) in src[line_start..].char_indices() {
+        if utf16 >= character || c == '\n' {
+            return line_start + i;
+        }
+        utf16 += c.len_utf16();
+    }
+    src.len()
+}
+
+/// Converts a byte offset into a lsp position (a line & a utf-16 character offset)
+pub fn offset_to_position(src: &str, offset: usize) -> JsonValue {
+    let before = &src[..offset.min(src.len())];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    object! {
+        line: before.matches('\n').count(),
+        character: before[line_start..].encode_utf16().count(),
+    }
+}
+
+/// Converts a byte span into a lsp range
+fn range(src: &str, span: ketchup::Span) -> JsonValue {
+    object! {
+        start: offset_to_position(src, span.start),
+        end: offset_to_position(src, span.end),
+    }
+}
@@ -1,45 +1,320 @@
-use std::fs;
+use std::{fs::{self, File}, io::{self, BufRead, BufWriter, Write}, ops::RangeInclusive, path::Path};
 use color_eyre::eyre::Context;
 use logos::Logos;
-use clap::Parser;
-use scrapile::{cli::Cli, lang::{error::Reportable, parser, targets, token::Token, typed}, scratch::add_console};
+use clap::{Parser, ValueEnum};
+use scrapile::{cli::{Cli, Command, EmitStage, SourceArgs}, compile::{self, CompileOptions, Stage}, lang::{debugger::Debugger, edition::Edition, error::{ReportConfig, Reportable}, fmt, interpreter::{self, RuntimeError}, parser, repl::{Repl, ReplError}, targets, token::Token, typed::root::Project}, manifest::Manifest, scratch::emulator, Diagnostics};
 
 /// Go through all the errors and report them
-fn throw_lang_error<T>(src: &str, src_id: &str, errors: &[impl Reportable]) -> T {
-    for error in errors {
-        error.report(src_id, src);
-    }
-
+fn throw_lang_error<T>(src: &str, src_id: &str, errors: &Diagnostics, config: &ReportConfig) -> T {
+    errors.report(src_id, src, config);
     std::process::exit(1);
 }
 
+/// Reads the contents of a source-code file
+fn read_src(path: &str) -> String {
+    fs::read_to_string(path)
+        .wrap_err_with(|| format!("while reading contents of source-code file `{path}`"))
+        .unwrap()
+}
+
+/// Determines the language edition of a source-code file (the cli takes priority over the project manifest)
+fn find_edition(path: &str, cli_edition: Option<Edition>) -> Edition {
+    let manifest = Manifest::find(path).unwrap();
+    cli_edition
+        .or(manifest.and_then(|(_, manifest)| manifest.edition))
+        .unwrap_or_default()
+}
+
 fn main() {
     // setup color-eyre
     color_eyre::install().unwrap();
 
     // parse the cli
     let cli = Cli::parse();
+    let config = cli.report_config();
+
+    match cli.command {
+        Command::Build { source, output_file, emit, charset } => build(&source, output_file.as_deref(), &emit, &charset, &config),
+        Command::Check { source } => { check(&source, &config); },
+        Command::Run { source, charset, emulate } => run(&source, &charset, emulate, &config),
+        Command::Debug { source, charset, breakpoints } => debug(&source, &charset, &breakpoints, &config),
+        Command::Test { source, charset } => test(&source, &charset, &config),
+        Command::Repl { edition, charset } => repl(edition.unwrap_or_default(), charset, &config),
+        Command::Fmt { files, check, edition } => format_files(&files, check, edition, &config),
+        Command::Lsp { edition } => lsp(edition),
+    }
+}
+
+/// Prints an intermediate compiler stage to stdout (if it was requested)
+fn emit_stage(emit: &[EmitStage], stage: EmitStage, contents: impl FnOnce() -> String) {
+    if !emit.contains(&stage) { return }
+
+    // only label the stages if there's multiple of them
+    let mut contents = contents();
+    if emit.len() > 1 {
+        contents = format!("// ---- {} ----\n{contents}", stage.to_possible_value().unwrap().get_name());
+    }
+
+    // stop quietly if stdout was closed early (like when piping into `head`)
+    if writeln!(io::stdout().lock(), "{contents}").is_err() {
+        std::process::exit(0);
+    }
+}
+
+/// Reads, parses and type-checks the input program (reporting any errors & warnings), returning it's source code & project
+fn check(source: &SourceArgs, config: &ReportConfig) -> (String, Project) {
+    let input_file = source.input_file.as_str();
 
     // read the input program file
-    let src = fs::read_to_string(&cli.input_file)
-        .wrap_err_with(|| format!("while reading contents of source-code file `{}`", cli.input_file))
-        .unwrap();
-    
-    // lex, parse, type-check and assemble the project
-    let mut tokens = Token::lexer(&src).spanned();
-    let roots = match parser::root::parse_root(&mut tokens) {
+    let src = read_src(input_file);
+    let edition = find_edition(input_file, source.edition);
+
+    compile::warnings(&src, edition).report(input_file, &src, config);
+
+    // parse and type-check the project
+    let roots = match compile::parse(&src, edition) {
+        Ok(ok) => ok,
+        Err(err) => throw_lang_error(&src, input_file, &err, config),
+    };
+    let project = match compile::type_check(&roots) {
         Ok(ok) => ok,
-        Err(err) => throw_lang_error(&src, &cli.input_file, &err),
+        Err(err) => throw_lang_error(&src, input_file, &err, config),
+    };
+
+    // denied lints stop compilation
+    let lints = compile::lint(&src, edition, &roots, &project, &source.lint_levels());
+    if lints.has_errors() {
+        throw_lang_error(&src, input_file, &lints, config)
+    }
+    lints.report(input_file, &src, config);
+
+    (src, project)
+}
+
+/// Compiles the input program into a scratch binary
+fn build(source: &SourceArgs, output_file: Option<&str>, emit: &[EmitStage], charset: &[RangeInclusive<u32>], config: &ReportConfig) {
+    // when emitting stages, the scratch binary is only written if it's explicitly asked for
+    let output_file = match (output_file, emit.is_empty()) {
+        (Some(output_file), _) => Some(output_file.to_string()),
+        (None, true) => Some(Path::new(&source.input_file).with_extension("sb3").to_string_lossy().into_owned()),
+        (None, false) => None,
     };
-    let project = match typed::root::wrap_root(&roots) {
+
+    let input_file = source.input_file.as_str();
+    let src = read_src(input_file);
+    let options = CompileOptions {
+        edition: find_edition(input_file, source.edition),
+        charset: charset.to_vec(),
+        lints: source.lint_levels(),
+    };
+
+    // compile the project (stages are emitted before any errors, to help with debugging)
+    emit_stage(emit, EmitStage::Tokens, || Token::lexer_with_extras(&src, options.edition.into())
+        .spanned()
+        .map(|(token, span)| format!("{span:?} {token:?}"))
+        .collect::<Vec<_>>()
+        .join("\n"));
+    let compiled = compile::compile_with_stages(input_file, &src, &options, |stage| match stage {
+        Stage::Ast(roots) => emit_stage(emit, EmitStage::Ast, || format!("{roots:#?}")),
+        Stage::Typed(project) => emit_stage(emit, EmitStage::Typed, || format!("{project:#?}")),
+        Stage::Assembly(assembly) => emit_stage(emit, EmitStage::Assembly, || format!("{assembly:#?}")),
+        Stage::ProjectJson(json) => emit_stage(emit, EmitStage::ProjectJson, || json.pretty(2)),
+    });
+    let compiled = match compiled {
         Ok(ok) => ok,
-        Err(err) => throw_lang_error(&src, &cli.input_file, &[err]),
+        Err(err) => throw_lang_error(&src, input_file, &err, config),
     };
-    let assembly = targets::scratch::translate(project);
-    let json = add_console("console", scrapile::scratch::assemble(assembly));
+    compiled.warnings.report(input_file, &src, config);
+
+    let Some(output_file) = output_file else { return };
 
     // write the assembled project to the output path
-    scrapile::scratch::write_to_zip(&cli.output_file, json)
-        .with_context(|| format!("while writing compiled scratch binary to output path `{}`", cli.output_file))
+    File::create(&output_file)
+        .and_then(|file| compiled.write_sb3(BufWriter::new(file)))
+        .with_context(|| format!("while writing compiled scratch binary to output path `{output_file}`"))
         .unwrap();
 }
+
+/// Runs the input program locally (or the compiled assembly in the scratch emulator)
+fn run(source: &SourceArgs, charset: &[RangeInclusive<u32>], emulate: bool, config: &ReportConfig) {
+    let (src, project) = check(source, config);
+
+    if emulate {
+        let assembly = targets::scratch::translate(project, &source.input_file, &src, charset);
+        match emulator::run(&assembly, "console", io::stdin().lock(), io::stdout().lock()) {
+            // the scratch target only stops everything when it panics
+            Ok(stage) if stage.stopped => std::process::exit(101),
+            Ok(_) => (),
+            Err(err) => exit_io_error(err),
+        }
+        return;
+    }
+
+    if let Err(err) = interpreter::run(&project, &source.input_file, &src, charset, io::stdin().lock(), io::stdout().lock()) {
+        exit_runtime_error(err, source, &src, config);
+    }
+}
+
+/// Runs the input program locally in the step debugger (which reads commands from stdin, like the program's input, & prints to stderr)
+fn debug(source: &SourceArgs, charset: &[RangeInclusive<u32>], breakpoints: &[usize], config: &ReportConfig) {
+    let (src, project) = check(source, config);
+
+    eprintln!("debugging `{}` (enter `help` for the commands)", source.input_file);
+    let edition = find_edition(&source.input_file, source.edition);
+    let mut debugger = Debugger::new(&src, &source.input_file, edition, breakpoints.iter().copied(), io::stderr());
+    for line in debugger.unreachable_breakpoints() {
+        eprintln!("warning: no statements start on line {line}, so the breakpoint on it won't be hit");
+    }
+    if let Err(err) = interpreter::run_with_hook(&project, &source.input_file, &src, charset, io::stdin().lock(), io::stdout().lock(), &mut debugger) {
+        exit_runtime_error(err, source, &src, config);
+    }
+}
+
+/// Exits after the input program stopped early
+fn exit_runtime_error(err: RuntimeError, source: &SourceArgs, src: &str, config: &ReportConfig) {
+    match err {
+        // panics exit with the same status as rust panics
+        RuntimeError::Panic { .. } => {
+            err.diagnostic().unwrap().report(&source.input_file, src, config);
+            std::process::exit(101);
+        },
+        RuntimeError::Io(err) => exit_io_error(err),
+        RuntimeError::Quit => std::process::exit(0),
+    }
+}
+
+/// Runs the tests of the input program locally, printing a summary of them
+fn test(source: &SourceArgs, charset: &[RangeInclusive<u32>], config: &ReportConfig) {
+    let (src, project) = check(source, config);
+
+    println!("running {} tests", project.tests.len());
+    let mut failures = Vec::new();
+    for (name, block) in project.tests.iter() {
+        // the output of tests is only shown if they fail
+        let mut output = Vec::new();
+        match interpreter::run_test(&project, block, &source.input_file, &src, charset, io::empty(), &mut output) {
+            Ok(()) => println!("test {name} ... ok"),
+            Err(err) => {
+                println!("test {name} ... FAILED");
+                failures.push((name, output, err));
+            },
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (name, output, err) in failures.iter() {
+            println!("\n---- {name} ----");
+            if !output.is_empty() {
+                print!("{}", String::from_utf8_lossy(output));
+            }
+            io::stdout().flush().unwrap();
+
+            match err {
+                RuntimeError::Panic { .. } => err.diagnostic().unwrap().report(&source.input_file, &src, config),
+                RuntimeError::Io(err) => eprintln!("error: while running the test: {err}"),
+                RuntimeError::Quit => unreachable!("tests are run without hooks"),
+            }
+        }
+    }
+
+    let passed = project.tests.len() - failures.len();
+    let result = if failures.is_empty() { "ok" } else { "FAILED" };
+    println!("\ntest result: {result}. {passed} passed; {} failed", failures.len());
+
+    // failed tests exit with the same status as rust's test harness
+    if !failures.is_empty() {
+        std::process::exit(101);
+    }
+}
+
+/// Reads entries from stdin & evaluates them until the end of the input, printing the value (and type) of each one
+fn repl(edition: Edition, charset: Vec<RangeInclusive<u32>>, config: &ReportConfig) {
+    let mut repl = Repl::new(edition, charset);
+    let mut stdin = io::stdin().lock();
+
+    loop {
+        // keep reading lines until the braces of the entry are closed
+        let mut entry = String::new();
+        loop {
+            print!("{}", if entry.is_empty() { ">> " } else { ".. " });
+            io::stdout().flush().unwrap();
+
+            match stdin.read_line(&mut entry) {
+                Ok(0) => {
+                    println!();
+                    return;
+                },
+                Ok(_) if repl.is_unfinished(&entry) => (),
+                Ok(_) => break,
+                Err(err) => exit_io_error(err),
+            }
+        }
+
+        let entry = entry.trim_end();
+        if entry.is_empty() { continue }
+
+        match repl.eval(entry, &mut stdin, io::stdout().lock()) {
+            Ok(Some((value, value_type))) => println!("{}: {value_type}", value.render(true)),
+            Ok(None) => (),
+            Err(ReplError::Compile(errors)) => errors.report("<repl>", repl.source(), config),
+            Err(ReplError::Runtime(err @ RuntimeError::Panic { .. })) => err.diagnostic().unwrap().report("<repl>", repl.source(), config),
+            Err(ReplError::Runtime(RuntimeError::Io(err))) => exit_io_error(err),
+            Err(ReplError::Runtime(RuntimeError::Quit)) => unreachable!("the repl runs without hooks"),
+        }
+    }
+}
+
+/// Exits after failing to read from stdin or write to stdout while running a program
+fn exit_io_error(err: io::Error) {
+    // stop quietly if stdout was closed early (like when piping into `head`)
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        std::process::exit(0);
+    }
+
+    eprintln!("error: while running the program: {err}");
+    std::process::exit(1);
+}
+
+/// Formats source-code files in-place (or only checks if they're formatted)
+fn format_files(files: &[String], check: bool, cli_edition: Option<Edition>, config: &ReportConfig) {
+    let mut failed = false;
+
+    for file in files {
+        let src = read_src(file);
+        let edition = find_edition(file, cli_edition);
+
+        // only format syntactically correct files
+        if let Err(errors) = parser::root::parse_root(&mut Token::lexer_with_extras(&src, edition.into()).spanned()) {
+            for error in errors {
+                error.report(file, &src, config);
+            }
+            failed = true;
+            continue;
+        }
+
+        let formatted = fmt::format(&src, edition);
+        if formatted == src { continue }
+
+        if check {
+            eprintln!("file `{file}` is not formatted");
+            failed = true;
+        } else {
+            fs::write(file, formatted)
+                .wrap_err_with(|| format!("while writing formatted source-code to `{file}`"))
+                .unwrap();
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Runs the language server over stdio
+fn lsp(edition: Option<Edition>) {
+    // exiting without a shutdown request is an error according to the spec
+    if !scrapile::lsp::run(edition).unwrap() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,51 @@
+//! The optional project manifest (`scrapile.json`), which is searched for in the source file's directory and it's parents
+
+use std::{fs, path::{Path, PathBuf}};
+use clap::ValueEnum;
+use color_eyre::eyre::{self, eyre, Context};
+use crate::lang::edition::Edition;
+
+/// The file name of the project manifest
+pub const MANIFEST_NAME: &str = "scrapile.json";
+
+/// The project manifest
+#[derive(Debug, Default)]
+pub struct Manifest {
+    /// The language edition of the project
+    pub edition: Option<Edition>,
+}
+
+impl Manifest {
+    /// Searches for the manifest of a source file and parses it (if there is one)
+    pub fn find(input_file: impl AsRef<Path>) -> eyre::Result<Option<(PathBuf, Manifest)>> {
+        let input_file = input_file.as_ref().canonicalize().unwrap_or_else(|_| input_file.as_ref().to_path_buf());
+
+        for dir in input_file.ancestors().skip(1) {
+            let path = dir.join(MANIFEST_NAME);
+            if !path.is_file() { continue }
+
+            let src = fs::read_to_string(&path)
+                .wrap_err_with(|| format!("while reading project manifest `{}`", path.display()))?;
+            let manifest = Self::parse(&src)
+                .wrap_err_with(|| format!("while parsing project manifest `{}`", path.display()))?;
+            return Ok(Some((path, manifest)));
+        }
+
+        Ok(None)
+    }
+
+    /// Parses the contents of a manifest
+    pub fn parse(src: &str) -> eyre::Result<Manifest> {
+        let json = json::parse(src)?;
+        let mut manifest = Manifest::default();
+
+        // the edition may be either a string or number (eg. `"2024"` or `2024`)
+        let edition = &json["edition"];
+        if !edition.is_null() {
+            let name = edition.as_str().map(str::to_string).unwrap_or_else(|| edition.dump());
+            manifest.edition = Some(Edition::from_str(&name, false).map_err(|_| eyre!("unknown edition `{name}`, expected either `2023` or `2024`"))?);
+        }
+
+        Ok(manifest)
+    }
+}
@@ -7,6 +7,7 @@ pub mod cond;
 pub mod statement;
 pub mod assembler;
 pub mod procedure;
+pub mod emulator;
 mod block;
 
 pub use expr::*;
@@ -15,13 +16,18 @@ pub use statement::*;
 pub use assembler::*;
 pub use procedure::*;
 
-use std::{fs::File, io::Write, path::Path};
+use std::{fs::File, io::{Seek, Write}, path::Path};
 use json::{array, object, JsonValue};
 use zip::{write::SimpleFileOptions, ZipWriter};
 
 /// Takes the json output of `assemble` and writes it to a zip file of the path specified
 pub fn write_to_zip(path: impl AsRef<Path>, json: JsonValue) -> Result<(), std::io::Error> {
-    let mut zip = ZipWriter::new(File::create(path)?);
+    write_sb3(File::create(path)?, &json)
+}
+
+/// Takes the json output of `assemble` and writes it as a `.sb3` (zip) to any writer
+pub fn write_sb3(writer: impl Write + Seek, json: &JsonValue) -> Result<(), std::io::Error> {
+    let mut zip = ZipWriter::new(writer);
 
     // write the json
     zip.start_file("project.json", SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated))?;
@@ -16,8 +16,11 @@ fn stmt_idx_to_id(idx: usize) -> String {
 pub struct Assembly {
     pub stmts: Vec<Statement>,
     pub variables: Vec<String>,
-    pub lists: Vec<String>,
+    /// List identifiers along with their initial contents
+    pub lists: Vec<(String, Vec<String>)>,
     pub procedures: Vec<Procedure>,
+    /// Additional backdrop names (all sharing the default backdrop's asset)
+    pub backdrops: Vec<String>,
 }
 
 /// The scratch equivelent of an 'assembler'; it takes in instructions that are very close to the scratch equivelant and generates the final `.sb3` project
@@ -79,13 +82,24 @@ pub fn assemble(assembly: Assembly) -> JsonValue {
             ""
         ];
     }
-    for list in assembly.lists.iter() {
+    for (list, contents) in assembly.lists.iter() {
         json["targets"][0]["lists"][list] = array! [
             **list,
-            []
+            contents.clone(),
         ];
     }
 
+    // insert the additional backdrops
+    for backdrop in assembly.backdrops.iter() {
+        json["targets"][0]["costumes"].push(object! {
+            name: **backdrop, dataFormat: "svg",
+            assetId: "cd21514d0531fdffb22204e0ec5ed84a",
+            md5ext: "cd21514d0531fdffb22204e0ec5ed84a.svg",
+            rotationCenterX: 240,
+            rotationCenterY: 180,
+        }).unwrap();
+    }
+
     // insert start block
     json["targets"][0]["blocks"]["startflag"] = object! {
         opcode: "event_whenflagclicked",
@@ -16,6 +16,12 @@ pub enum Condition {
     And(Box<Condition>, Box<Condition>),
     Or(Box<Condition>, Box<Condition>),
     Not(Box<Condition>),
+
+    // list conditions
+    ListContains {
+        ident: String,
+        item: Expr,
+    },
 }
 
 /// Parses a scratch condition and outputs the generated json
@@ -163,5 +169,29 @@ pub(super) fn parse_cond(cond: Condition, expr_blocks: &mut Vec<JsonValue>) -> J
             expr_blocks.push(json);
             expr_idx_to_id(expr_blocks.len()-1).into()
         },
+        C::ListContains { ident, item } => {
+            let item = parse_expr(item, expr_blocks);
+            let json = object! {
+                opcode: "data_listcontainsitem",
+                next: null,
+                parent: null,
+                inputs: {
+                    ITEM: [
+                        1,
+                        item,
+                    ],
+                },
+                fields: {
+                    LIST: [
+                        ident,
+                        "",
+                    ],
+                },
+                shadow: false,
+                topLevel: false,
+            };
+            expr_blocks.push(json);
+            expr_idx_to_id(expr_blocks.len()-1).into()
+        },
     }
 }
@@ -0,0 +1,452 @@
+//! A headless executor for scratch assembly that mimics the scratch vm, to check the generated code without a browser
+//!
+//! values are cast between numbers, strings & booleans the way the vm's `Cast` does, lists are 1-based, `operator_equals`
+//! is case-insensitive and `NaN`s read as `0` (except that strings are indexed by characters instead of utf-16 code units)
+
+use std::{cmp::Ordering, collections::HashMap, fmt::Display, io::{self, BufRead, Write}, time::Instant};
+use super::{Assembly, Condition, Expr, Procedure, Statement};
+
+/// The maximum amount of items in a list (pushes & inserts past it are ignored)
+pub const LIST_ITEM_LIMIT: usize = 200000;
+
+/// A scratch value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// Casts the value to a number (`NaN` becomes `0`)
+    pub fn to_number(&self) -> f64 {
+        let num = match self {
+            Value::Number(num) => *num,
+            Value::String(string) => js_number(string),
+            Value::Bool(bool) => *bool as u8 as f64,
+        };
+
+        if num.is_nan() { 0.0 } else { num }
+    }
+
+    /// Casts the value to a boolean (`""`, `"0"` & `"false"` are false)
+    pub fn to_bool(&self) -> bool {
+        match self {
+            Value::Number(num) => *num != 0.0 && !num.is_nan(),
+            Value::String(string) => !(string.is_empty() || string == "0" || string.eq_ignore_ascii_case("false")),
+            Value::Bool(bool) => *bool,
+        }
+    }
+
+    /// Compares two values; numerically if they both look like numbers, otherwise as case-insensitive strings
+    pub fn compare(&self, other: &Value) -> Ordering {
+        let (lhs, rhs) = (self.loose_number(), other.loose_number());
+        if lhs.is_nan() || rhs.is_nan() {
+            return self.to_string().to_lowercase().cmp(&other.to_string().to_lowercase());
+        }
+
+        lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal)
+    }
+
+    /// The value as a number for comparisons (whitespace-only strings aren't numbers)
+    fn loose_number(&self) -> f64 {
+        match self {
+            Value::Number(num) => *num,
+            Value::String(string) if string.trim().is_empty() => f64::NAN,
+            Value::String(string) => js_number(string),
+            Value::Bool(bool) => *bool as u8 as f64,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(num) => write!(f, "{}", number_to_string(*num)),
+            Value::String(string) => write!(f, "{string}"),
+            Value::Bool(bool) => write!(f, "{bool}"),
+        }
+    }
+}
+
+/// Converts a number into a string the same way javascript (and so scratch) does
+pub fn number_to_string(num: f64) -> String {
+    if num.is_nan() {
+        return "NaN".to_string();
+    }
+    if num.is_infinite() {
+        return if num > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if num == 0.0 {
+        return "0".to_string(); // includes `-0`
+    }
+
+    // very large & very small numbers use exponent notation
+    if !(1e-6..1e21).contains(&num.abs()) {
+        let string = format!("{num:e}");
+        return match string.split_once('e') {
+            Some((mantissa, exponent)) if !exponent.starts_with('-') => format!("{mantissa}e+{exponent}"),
+            _ => string,
+        };
+    }
+
+    num.to_string()
+}
+
+/// Converts a string into a number the same way javascript's `Number()` does (`NaN` if it isn't a number)
+fn js_number(string: &str) -> f64 {
+    let string = string.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}');
+    if string.is_empty() {
+        return 0.0;
+    }
+
+    // integers with a radix prefix (which can't have a sign)
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = string.strip_prefix(prefix).or_else(|| string.strip_prefix(&prefix.to_uppercase())) {
+            return u64::from_str_radix(digits, radix).map(|num| num as f64).unwrap_or(f64::NAN);
+        }
+    }
+
+    match string {
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        // rust also accepts things like `inf` & `nan`, which javascript doesn't
+        _ if !string.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')) => f64::NAN,
+        _ => string.parse().unwrap_or(f64::NAN),
+    }
+}
+
+/// The state of the stage after running some assembly
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub variables: HashMap<String, Value>,
+    pub lists: HashMap<String, Vec<Value>>,
+    /// The names of the backdrops (the first is always the default `backdrop1`)
+    pub backdrops: Vec<String>,
+    /// The index of the current backdrop
+    pub backdrop: usize,
+    /// If the program was stopped with a `stop all` block
+    pub stopped: bool,
+}
+
+/// A list index after casting
+enum ListIndex {
+    Index(usize),
+    All,
+    Invalid,
+}
+
+/// Why the program stopped early
+enum Halt {
+    StopAll,
+    Io(io::Error),
+}
+
+impl From<io::Error> for Halt {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Runs the assembly from the green flag, answering `ask` blocks with lines from `input`
+///
+/// the prompts of `ask` blocks are written to `output` without a newline, and items pushed to the `console` list are also written to `output` as lines (like the console's list monitor)
+pub fn run(assembly: &Assembly, console: &str, input: impl BufRead, output: impl Write) -> io::Result<Stage> {
+    let mut emulator = Emulator {
+        stage: Stage {
+            variables: assembly.variables.iter().map(|ident| (ident.clone(), Value::String(String::new()))).collect(),
+            lists: assembly.lists.iter()
+                .map(|(ident, contents)| (ident.clone(), contents.iter().cloned().map(Value::String).collect()))
+                .collect(),
+            backdrops: std::iter::once("backdrop1".to_string()).chain(assembly.backdrops.iter().cloned()).collect(),
+            backdrop: 0,
+            stopped: false,
+        },
+        procedures: assembly.procedures.iter().map(|Procedure { ident, body }| (ident.as_str(), body.as_slice())).collect(),
+        answer: String::new(),
+        console,
+        input,
+        output,
+        start: Instant::now(),
+        seed: 0x2545f4914f6cdd1d,
+    };
+
+    match emulator.stmts(&assembly.stmts) {
+        Ok(()) => (),
+        Err(Halt::StopAll) => emulator.stage.stopped = true,
+        Err(Halt::Io(err)) => return Err(err),
+    }
+    emulator.output.flush()?;
+
+    Ok(emulator.stage)
+}
+
+/// A running scratch project
+struct Emulator<'a, R: BufRead, W: Write> {
+    stage: Stage,
+    procedures: HashMap<&'a str, &'a [Statement]>,
+    /// The answer to the last `ask` block
+    answer: String,
+    console: &'a str,
+    input: R,
+    output: W,
+    /// When the green flag was clicked (for the timer)
+    start: Instant,
+    /// The state of the random number generator (for `random` list indices)
+    seed: u64,
+}
+
+impl<R: BufRead, W: Write> Emulator<'_, R, W> {
+    fn stmts(&mut self, stmts: &[Statement]) -> Result<(), Halt> {
+        for stmt in stmts {
+            self.stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: &Statement) -> Result<(), Halt> {
+        use Statement as S;
+
+        match stmt {
+            S::CallProcedure { ident } => {
+                // calls to procedures that don't exist do nothing
+                if let Some(body) = self.procedures.get(ident.as_str()).copied() {
+                    self.stmts(body)?;
+                }
+            },
+            S::Ask { prompt } => {
+                // the prompt is shown in a speech bubble, which is the closest thing to writing it out
+                let prompt = self.expr(prompt);
+                write!(self.output, "{prompt}")?;
+                self.output.flush()?;
+
+                // the end of the input is treated as an empty answer
+                let mut answer = String::new();
+                self.input.read_line(&mut answer)?;
+                let answer = answer.strip_suffix('\n').unwrap_or(&answer);
+                self.answer = answer.strip_suffix('\r').unwrap_or(answer).to_string();
+            },
+            S::SetVar { ident, value } => {
+                let value = self.expr(value);
+                self.stage.variables.insert(ident.clone(), value);
+            },
+            S::PushList { ident, value } => {
+                let value = self.expr(value);
+                if ident == self.console {
+                    writeln!(self.output, "{value}")?;
+                }
+
+                let list = self.list_mut(ident);
+                if list.len() < LIST_ITEM_LIMIT {
+                    list.push(value);
+                }
+            },
+            S::RemoveList { ident, idx } => {
+                let idx = self.expr(idx);
+                match self.list_index(&idx, self.list(ident).len(), true) {
+                    ListIndex::Index(idx) => { self.list_mut(ident).remove(idx - 1); },
+                    ListIndex::All => self.list_mut(ident).clear(),
+                    ListIndex::Invalid => (),
+                }
+            },
+            S::InsertList { ident, value, idx } => {
+                let value = self.expr(value);
+                let idx = self.expr(idx);
+                // items can also be inserted at the end of the list
+                if let ListIndex::Index(idx) = self.list_index(&idx, self.list(ident).len() + 1, false) {
+                    let list = self.list_mut(ident);
+                    if list.len() < LIST_ITEM_LIMIT {
+                        list.insert(idx - 1, value);
+                    }
+                }
+            },
+            S::ReplaceList { ident, value, idx } => {
+                let value = self.expr(value);
+                let idx = self.expr(idx);
+                if let ListIndex::Index(idx) = self.list_index(&idx, self.list(ident).len(), false) {
+                    self.list_mut(ident)[idx - 1] = value;
+                }
+            },
+            S::ClearList { ident } => self.list_mut(ident).clear(),
+            S::SwitchBackdrop { backdrop } => {
+                let backdrop = self.expr(backdrop);
+                self.switch_backdrop(backdrop);
+            },
+            S::StopAll => return Err(Halt::StopAll),
+            S::If { condition, body } => {
+                if self.cond(condition) {
+                    self.stmts(body)?;
+                }
+            },
+            S::IfElse { condition, body, otherwise } => {
+                if self.cond(condition) {
+                    self.stmts(body)?;
+                } else {
+                    self.stmts(otherwise)?;
+                }
+            },
+            S::RepeatUntil { condition, body } => {
+                while !self.cond(condition) {
+                    self.stmts(body)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn cond(&mut self, cond: &Condition) -> bool {
+        use Condition as C;
+
+        match cond {
+            C::GreaterThan(lhs, rhs) => self.expr(lhs).compare(&self.expr(rhs)) == Ordering::Greater,
+            C::LessThan(lhs, rhs) => self.expr(lhs).compare(&self.expr(rhs)) == Ordering::Less,
+            C::EqualTo(lhs, rhs) => self.expr(lhs).compare(&self.expr(rhs)) == Ordering::Equal,
+
+            // both sides are always evaluated
+            C::And(lhs, rhs) => {
+                let lhs = self.cond(lhs);
+                self.cond(rhs) && lhs
+            },
+            C::Or(lhs, rhs) => {
+                let lhs = self.cond(lhs);
+                self.cond(rhs) || lhs
+            },
+            C::Not(cond) => !self.cond(cond),
+
+            C::ListContains { ident, item } => {
+                let item = self.expr(item);
+                self.list(ident).iter().any(|value| value.compare(&item) == Ordering::Equal)
+            },
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Value {
+        use Expr as E;
+
+        let number = |this: &mut Self, expr: &Expr| this.expr(expr).to_number();
+
+        match expr {
+            // non-finite numbers are written to the project as strings
+            E::Float(num) | E::PosFloat(num) if !num.is_finite() => Value::String(number_to_string(*num)),
+            E::Float(num) | E::PosFloat(num) => Value::Number(*num),
+            E::PosInteger(num) => Value::Number(*num as f64),
+            E::Integer(num) => Value::Number(*num as f64),
+            E::String(string) => Value::String(string.clone()),
+
+            E::Condition(cond) => Value::Bool(self.cond(cond)),
+            E::Add(lhs, rhs) => Value::Number(number(self, lhs) + number(self, rhs)),
+            E::Sub(lhs, rhs) => Value::Number(number(self, lhs) - number(self, rhs)),
+            E::Mul(lhs, rhs) => Value::Number(number(self, lhs) * number(self, rhs)),
+            E::Div(lhs, rhs) => Value::Number(number(self, lhs) / number(self, rhs)),
+            E::Mod(lhs, rhs) => {
+                let (lhs, rhs) = (number(self, lhs), number(self, rhs));
+                // the result takes the sign of the divisor
+                let result = lhs % rhs;
+                Value::Number(if result / rhs < 0.0 { result + rhs } else { result })
+            },
+            E::Concat(lhs, rhs) => {
+                let lhs = self.expr(lhs).to_string();
+                Value::String(lhs + &self.expr(rhs).to_string())
+            },
+
+            E::Answer => Value::String(self.answer.clone()),
+            E::Timer => Value::Number(self.start.elapsed().as_secs_f64()),
+
+            // variables that don't exist are created with a value of `0`
+            E::Variable { ident } => self.stage.variables.entry(ident.clone()).or_insert(Value::Number(0.0)).clone(),
+            E::ListElement { ident, idx } => {
+                let idx = self.expr(idx);
+                match self.list_index(&idx, self.list(ident).len(), false) {
+                    ListIndex::Index(idx) => self.list(ident)[idx - 1].clone(),
+                    _ => Value::String(String::new()),
+                }
+            },
+            E::ListLength { ident } => Value::Number(self.list(ident).len() as f64),
+            E::ListItemNum { ident, item } => {
+                // missing items have an index of `0`
+                let item = self.expr(item);
+                let idx = self.list(ident).iter().position(|value| value.compare(&item) == Ordering::Equal);
+                Value::Number(idx.map(|idx| idx + 1).unwrap_or(0) as f64)
+            },
+
+            E::BackdropNumber => Value::Number(self.stage.backdrop as f64 + 1.0),
+            E::BackdropName => Value::String(self.stage.backdrops[self.stage.backdrop].clone()),
+
+            E::StringElement { string, idx } => {
+                let idx = number(self, idx) - 1.0;
+                let string = self.expr(string).to_string();
+                let c = if idx < 0.0 { None } else { string.chars().nth(idx as usize) };
+                Value::String(c.map(String::from).unwrap_or_default())
+            },
+            E::StringLength { string } => Value::Number(self.expr(string).to_string().chars().count() as f64),
+        }
+    }
+
+    /// Gets a list (lists that don't exist are empty)
+    fn list(&self, ident: &str) -> &[Value] {
+        self.stage.lists.get(ident).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Gets a list mutably (creating it if it doesn't exist)
+    fn list_mut(&mut self, ident: &str) -> &mut Vec<Value> {
+        self.stage.lists.entry(ident.to_string()).or_default()
+    }
+
+    /// Casts a value to a 1-based list index, including the special `all`, `last` & `random` indices
+    fn list_index(&mut self, idx: &Value, len: usize, accept_all: bool) -> ListIndex {
+        if let Value::String(special) = idx {
+            match special.as_str() {
+                "all" if accept_all => return ListIndex::All,
+                "all" => return ListIndex::Invalid,
+                "last" if len > 0 => return ListIndex::Index(len),
+                "last" => return ListIndex::Invalid,
+                "random" | "any" if len > 0 => return ListIndex::Index(self.random(len)),
+                "random" | "any" => return ListIndex::Invalid,
+                _ => (),
+            }
+        }
+
+        let idx = idx.to_number().floor();
+        if idx < 1.0 || idx > len as f64 {
+            return ListIndex::Invalid;
+        }
+        ListIndex::Index(idx as usize)
+    }
+
+    /// A pseudo-random number from `1` to `max` (with xorshift)
+    fn random(&mut self, max: usize) -> usize {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed % max as u64) as usize + 1
+    }
+
+    /// Switches to a backdrop by it's name (case-sensitive), or otherwise by it's number
+    fn switch_backdrop(&mut self, backdrop: Value) {
+        let count = self.stage.backdrops.len();
+        let number = match backdrop {
+            Value::Number(num) => num,
+            backdrop => {
+                let name = backdrop.to_string();
+                if let Some(idx) = self.stage.backdrops.iter().position(|backdrop| *backdrop == name) {
+                    self.stage.backdrop = idx;
+                    return;
+                }
+
+                match name.as_str() {
+                    "next backdrop" => (self.stage.backdrop + 2) as f64,
+                    "previous backdrop" => self.stage.backdrop as f64,
+                    "random backdrop" => self.random(count) as f64,
+                    _ if name.trim().is_empty() || js_number(&name).is_nan() => return,
+                    _ => js_number(&name),
+                }
+            },
+        };
+
+        // backdrop numbers wrap around
+        let idx = (number - 1.0).round();
+        let idx = if idx.is_finite() { idx } else { 0.0 };
+        self.stage.backdrop = idx.rem_euclid(count as f64) as usize;
+    }
+}
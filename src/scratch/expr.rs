@@ -33,6 +33,14 @@ pub enum Expr {
         idx: Box<Expr>,
     },
     ListLength { ident: String },
+    ListItemNum {
+        ident: String,
+        item: Box<Expr>,
+    },
+
+    // looks operations
+    BackdropNumber,
+    BackdropName,
 
     // string operations
     StringElement {
@@ -49,6 +57,7 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
 
     match expr {
         // basic numbers & strings
+        E::Float(num) if !num.is_finite() => array![ 4, num.to_string().replace("inf", "Infinity") ], // json can't represent these as numbers
         E::Float(num) => array![ 4, num ],
         E::PosFloat(num) => array![ 5, num ],
         E::PosInteger(num) => array![ 6, num ],
@@ -57,6 +66,9 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
 
         // concat
         E::Concat(lhs, rhs) => {
+            let lhs = parse_expr(*lhs, expr_blocks);
+            let rhs = parse_expr(*rhs, expr_blocks);
+
             let json = object! {
                 opcode: "operator_join",
                 next: null,
@@ -64,11 +76,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     STRING1: [
                         1,
-                        parse_expr((*lhs).clone(), expr_blocks),
+                        lhs,
                     ],
                     STRING2: [
                         1,
-                        parse_expr((*rhs).clone(), expr_blocks),
+                        rhs,
                     ],
                 },
                 fields: {},
@@ -81,6 +93,9 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
 
         // maths operations
         E::Add(lhs, rhs) => {
+            let lhs = parse_expr(*lhs, expr_blocks);
+            let rhs = parse_expr(*rhs, expr_blocks);
+
             let json = object! {
                 opcode: "operator_add",
                 next: null,
@@ -88,11 +103,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     NUM1: [
                         1,
-                        parse_expr((*lhs).clone(), expr_blocks),
+                        lhs,
                     ],
                     NUM2: [
                         1,
-                        parse_expr((*rhs).clone(), expr_blocks),
+                        rhs,
                     ],
                 },
                 fields: {},
@@ -104,6 +119,9 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
             expr_idx_to_id(expr_blocks.len()-1).into()
         },
         E::Sub(lhs, rhs) => {
+            let lhs = parse_expr(*lhs, expr_blocks);
+            let rhs = parse_expr(*rhs, expr_blocks);
+
             let json = object! {
                 opcode: "operator_subtract",
                 next: null,
@@ -111,11 +129,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     NUM1: [
                         1,
-                        parse_expr((*lhs).clone(), expr_blocks),
+                        lhs,
                     ],
                     NUM2: [
                         1,
-                        parse_expr((*rhs).clone(), expr_blocks),
+                        rhs,
                     ],
                 },
                 fields: {},
@@ -127,6 +145,9 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
             expr_idx_to_id(expr_blocks.len()-1).into()
         },
         E::Mul(lhs, rhs) => {
+            let lhs = parse_expr(*lhs, expr_blocks);
+            let rhs = parse_expr(*rhs, expr_blocks);
+
             let json = object! {
                 opcode: "operator_multiply",
                 next: null,
@@ -134,11 +155,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     NUM1: [
                         1,
-                        parse_expr((*lhs).clone(), expr_blocks),
+                        lhs,
                     ],
                     NUM2: [
                         1,
-                        parse_expr((*rhs).clone(), expr_blocks),
+                        rhs,
                     ],
                 },
                 fields: {},
@@ -150,6 +171,9 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
             expr_idx_to_id(expr_blocks.len()-1).into()
         },
         E::Div(lhs, rhs) => {
+            let lhs = parse_expr(*lhs, expr_blocks);
+            let rhs = parse_expr(*rhs, expr_blocks);
+
             let json = object! {
                 opcode: "operator_divide",
                 next: null,
@@ -157,11 +181,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     NUM1: [
                         1,
-                        parse_expr((*lhs).clone(), expr_blocks),
+                        lhs,
                     ],
                     NUM2: [
                         1,
-                        parse_expr((*rhs).clone(), expr_blocks),
+                        rhs,
                     ],
                 },
                 fields: {},
@@ -173,6 +197,9 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
             expr_idx_to_id(expr_blocks.len()-1).into()
         },
         E::Mod(lhs, rhs) => {
+            let lhs = parse_expr(*lhs, expr_blocks);
+            let rhs = parse_expr(*rhs, expr_blocks);
+
             let json = object! {
                 opcode: "operator_mod",
                 next: null,
@@ -180,11 +207,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     NUM1: [
                         1,
-                        parse_expr((*lhs).clone(), expr_blocks),
+                        lhs,
                     ],
                     NUM2: [
                         1,
-                        parse_expr((*rhs).clone(), expr_blocks),
+                        rhs,
                     ],
                 },
                 fields: {},
@@ -230,6 +257,8 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
         // variables and lists
         E::Variable { ident } => array![ 12, ident, "" ],
         E::ListElement { ident, idx } => {
+            let idx = parse_expr(*idx, expr_blocks);
+
             let json = object! {
                 opcode: "data_itemoflist",
                 next: null,
@@ -237,7 +266,7 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     INDEX: [
                         1,
-                        parse_expr((*idx).clone(), expr_blocks),
+                        idx,
                     ]
                 },
                 fields: {
@@ -269,10 +298,79 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
             expr_blocks.push(json);
 
             expr_idx_to_id(expr_blocks.len()-1).into()
-        }
+        },
+        E::ListItemNum { ident, item } => {
+            let item = parse_expr(*item, expr_blocks);
+
+            let json = object! {
+                opcode: "data_itemnumoflist",
+                next: null,
+                parent: null,
+                inputs: {
+                    ITEM: [
+                        1,
+                        item,
+                    ],
+                },
+                fields: {
+                    LIST: [
+                        ident,
+                        "",
+                    ],
+                },
+                shadow: false,
+                topLevel: false,
+            };
+            expr_blocks.push(json);
+
+            expr_idx_to_id(expr_blocks.len()-1).into()
+        },
+
+        // looks operations
+        E::BackdropNumber => {
+            let json = object! {
+                opcode: "looks_backdropnumbername",
+                next: null,
+                parent: null,
+                inputs: {},
+                fields: {
+                    NUMBER_NAME: [
+                        "number",
+                        null,
+                    ],
+                },
+                shadow: false,
+                topLevel: false,
+            };
+            expr_blocks.push(json);
+
+            expr_idx_to_id(expr_blocks.len()-1).into()
+        },
+        E::BackdropName => {
+            let json = object! {
+                opcode: "looks_backdropnumbername",
+                next: null,
+                parent: null,
+                inputs: {},
+                fields: {
+                    NUMBER_NAME: [
+                        "name",
+                        null,
+                    ],
+                },
+                shadow: false,
+                topLevel: false,
+            };
+            expr_blocks.push(json);
+
+            expr_idx_to_id(expr_blocks.len()-1).into()
+        },
 
         // string operations
         E::StringElement { string, idx } => {
+            let idx = parse_expr(*idx, expr_blocks);
+            let string = parse_expr(*string, expr_blocks);
+
             let json = object! {
                 opcode: "operator_letter_of",
                 next: null,
@@ -280,11 +378,11 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     LETTER: [
                         1,
-                        parse_expr((*idx).clone(), expr_blocks),
+                        idx,
                     ],
                     STRING: [
                         1,
-                        parse_expr((*string).clone(), expr_blocks),
+                        string,
                     ],
                 },
                 fields: {},
@@ -296,6 +394,8 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
             expr_idx_to_id(expr_blocks.len()-1).into()
         },
         E::StringLength { string } => {
+            let string = parse_expr(*string, expr_blocks);
+
             let json = object! {
                 opcode: "operator_length",
                 next: null,
@@ -303,7 +403,7 @@ pub(super) fn parse_expr(expr: Expr, expr_blocks: &mut Vec<JsonValue>) -> JsonVa
                 inputs: {
                     STRING: [
                         1,
-                        parse_expr((*string).clone(), expr_blocks),
+                        string,
                     ],
                 },
                 fields: {},
@@ -36,6 +36,8 @@ pub enum Statement {
     },
     ClearList { ident: String },
 
+    SwitchBackdrop { backdrop: Expr },
+
     StopAll,
 
     If {
@@ -60,6 +62,8 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
     match stmt {
         S::CallProcedure { ident } => call_procedure(&ident),
         S::PushList { ident, value } => {
+            let value = parse_expr(value, expr_blocks);
+
             object! {
                 opcode: "data_addtolist",
                 next: null, // gets replaced later
@@ -67,7 +71,7 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
                 inputs: {
                     ITEM: [
                         1,
-                        parse_expr(value.clone(), expr_blocks),
+                        value,
                     ],
                 },
                 fields: {
@@ -93,16 +97,19 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
             }
         },
         S::InsertList { ident, value, idx } => {
+            let value = parse_expr(value, expr_blocks);
+            let idx = parse_expr(idx, expr_blocks);
+
             object! {
                 opcode: "data_insertatlist",
                 inputs: {
                     ITEM: [
                         1,
-                        parse_expr(value.clone(), expr_blocks),
+                        value,
                     ],
                     INDEX: [
                         1,
-                        parse_expr(idx.clone(), expr_blocks),
+                        idx,
                     ],
                 },
                 fields: {
@@ -114,16 +121,19 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
             }
         },
         S::ReplaceList { ident, value, idx } => {
+            let value = parse_expr(value, expr_blocks);
+            let idx = parse_expr(idx, expr_blocks);
+
             object! {
                 opcode: "data_replaceitemoflist",
                 inputs: {
                     ITEM: [
                         1,
-                        parse_expr(value.clone(), expr_blocks),
+                        value,
                     ],
                     INDEX: [
                         1,
-                        parse_expr(idx.clone(), expr_blocks),
+                        idx,
                     ],
                 },
                 fields: {
@@ -135,12 +145,14 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
             }
         },
         S::RemoveList { ident, idx } => {
+            let idx = parse_expr(idx, expr_blocks);
+
             object! {
                 opcode: "data_deleteoflist",
                 inputs: {
                     INDEX: [
                         1,
-                        parse_expr(idx.clone(), expr_blocks),
+                        idx,
                     ],
                 },
                 fields: {
@@ -152,12 +164,14 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
             }
         },
         S::SetVar { ident, value } => {
+            let value = parse_expr(value, expr_blocks);
+
             object! {
                 opcode: "data_setvariableto",
                 inputs: {
                     VALUE: [
                         1,
-                        parse_expr(value.clone(), expr_blocks),
+                        value,
                     ],
                 },
                 fields: {
@@ -169,12 +183,28 @@ pub(super) fn parse_stmt(stmt: Statement, expr_blocks: &mut Vec<JsonValue>) -> J
             }
         },
         S::Ask { prompt } => {
+            let prompt = parse_expr(prompt, expr_blocks);
+
             object! {
                 opcode: "sensing_askandwait",
                 inputs: {
                     QUESTION: [
                         1,
-                        parse_expr(prompt.clone(), expr_blocks),
+                        prompt,
+                    ],
+                },
+                fields: {},
+            }
+        },
+        S::SwitchBackdrop { backdrop } => {
+            let backdrop = parse_expr(backdrop, expr_blocks);
+
+            object! {
+                opcode: "looks_switchbackdropto",
+                inputs: {
+                    BACKDROP: [
+                        1,
+                        backdrop,
                     ],
                 },
                 fields: {},
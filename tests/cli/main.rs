@@ -0,0 +1,57 @@
+//! End-to-end tests of the `scrapile` binary's subcommands
+
+use std::{fs, path::PathBuf, process::{Command, Output}};
+
+/// Writes a program to the test's temporary directory & runs a `scrapile` subcommand on it
+fn scrapile(subcommand: &str, name: &str, src: &str) -> Output {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, src).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_scrapile"))
+        .args([subcommand, "--edition", "2024"])
+        .arg(&path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_reports_passing_and_failing_tests() {
+    let output = scrapile("test", "tests.srpl", r#"
+test fn passes() -> nil {
+    assert_eq!(1 + 1, 2);
+    assert_eq!([1, 2], [1, 2]);
+}
+
+test fn fails() -> nil {
+    println!("only shown on failure");
+    assert_eq!([1, 2], [3]);
+}
+
+main {}
+"#);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(101), "stdout:\n{stdout}\nstderr:\n{stderr}");
+    assert!(stdout.contains("running 2 tests"), "{stdout}");
+    assert!(stdout.contains("test passes ... ok"), "{stdout}");
+    assert!(stdout.contains("test fails ... FAILED"), "{stdout}");
+    assert!(stdout.contains("---- fails ----\nonly shown on failure"), "{stdout}");
+    assert!(stdout.contains("test result: FAILED. 1 passed; 1 failed"), "{stdout}");
+    assert!(stderr.contains("left: [1, 2]"), "{stderr}");
+}
+
+#[test]
+fn test_passes_without_failures() {
+    let output = scrapile("test", "passing.srpl", r#"
+test fn passes() -> nil {
+    assert!(true);
+}
+
+main {}
+"#);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("test result: ok. 1 passed; 0 failed"), "{stdout}");
+}
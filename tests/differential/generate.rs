@@ -0,0 +1,435 @@
+//! A generator of random (but well-typed & terminating) programs
+//!
+//! the programs steer clear of the places where scratch is *meant* to behave differently to the interpreter:
+//! - strings never spell out `true` or `false`, which scratch reads as booleans
+//! - numbers are only ever divided by non-zero literals, so there are no `NaN`s
+//! - loops always count up to a small literal and functions only call functions defined before them, so every program halts
+
+use std::fmt::Write;
+
+/// Generates the source code of a random program from a seed
+pub fn program(seed: u64) -> String {
+    let mut generator = Generator {
+        rng: Rng::new(seed),
+        scopes: Vec::new(),
+        funcs: Vec::new(),
+        next_ident: 0,
+    };
+
+    let mut src = String::new();
+    for _ in 0..generator.rng.below(4) {
+        src.push_str(&generator.func());
+        src.push('\n');
+    }
+
+    generator.scopes.push(Vec::new());
+    let count = 4 + generator.rng.below(12);
+    let body = generator.stmts(1, count);
+    generator.scopes.pop();
+    writeln!(src, "main {{\n{body}}}").unwrap();
+
+    src
+}
+
+/// A small xorshift random number generator (so the programs are the same on every run & platform)
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // splitmix the seed so neighbouring seeds don't start out similar (and so it's never zero)
+        let mut state = seed.wrapping_add(0x9e3779b97f4a7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d049bb133111eb);
+        Self((state ^ (state >> 31)) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A random number within `0..max`
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+
+    /// `true` one in `n` times
+    fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Num,
+    Str,
+    Bool,
+    NumList,
+    StrList,
+}
+
+impl Type {
+    const SCALARS: &'static [Type] = &[Type::Num, Type::Str, Type::Bool];
+    const ALL: &'static [Type] = &[Type::Num, Type::Str, Type::Bool, Type::NumList, Type::StrList];
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Num => "num",
+            Type::Str => "str",
+            Type::Bool => "bool",
+            Type::NumList => "[num]",
+            Type::StrList => "[str]",
+        }
+    }
+
+    fn item(self) -> Option<Type> {
+        match self {
+            Type::NumList => Some(Type::Num),
+            Type::StrList => Some(Type::Str),
+            _ => None,
+        }
+    }
+}
+
+struct Var {
+    ident: String,
+    var_type: Type,
+    mutable: bool,
+}
+
+struct Func {
+    ident: String,
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+struct Generator {
+    rng: Rng,
+    /// The variables in scope, innermost scope last
+    scopes: Vec<Vec<Var>>,
+    /// The functions that can be called
+    funcs: Vec<Func>,
+    next_ident: usize,
+}
+
+impl Generator {
+    fn ident(&mut self, prefix: &str) -> String {
+        self.next_ident += 1;
+        format!("{prefix}{}", self.next_ident)
+    }
+
+    /// A random variable in scope that matches the filter
+    fn var(&mut self, filter: impl Fn(&Var) -> bool) -> Option<(String, Type)> {
+        let vars = self.scopes.iter().flatten().filter(|var| filter(var)).collect::<Vec<_>>();
+        (!vars.is_empty()).then(|| {
+            let var = vars[self.rng.below(vars.len())];
+            (var.ident.clone(), var.var_type)
+        })
+    }
+
+    fn func(&mut self) -> String {
+        let ident = self.ident("f");
+        let return_type = self.rng.pick(Type::SCALARS);
+
+        let mut params = Vec::new();
+        let mut scope = Vec::new();
+        for _ in 0..self.rng.below(4) {
+            let var_type = self.rng.pick(Type::ALL);
+            scope.push(Var { ident: self.ident("p"), var_type, mutable: false });
+            params.push(var_type);
+        }
+        let signature = scope.iter().map(|var| format!("{}: {}", var.ident, var.var_type.name())).collect::<Vec<_>>().join(", ");
+
+        self.scopes.push(scope);
+        let count = self.rng.below(4);
+        let body = self.stmts(1, count);
+        let tail = self.expr(return_type, 3);
+        self.scopes.pop();
+
+        // functions are only callable after they're generated, so there's never any recursion
+        self.funcs.push(Func { ident: ident.clone(), params, return_type });
+        format!("fn {ident}({signature}) -> {} {{\n{body}\t{tail}\n}}\n", return_type.name())
+    }
+
+    fn stmts(&mut self, indent: usize, count: usize) -> String {
+        let mut stmts = String::new();
+        for _ in 0..count {
+            let stmt = self.stmt(indent);
+            writeln!(stmts, "{}{stmt};", "\t".repeat(indent)).unwrap();
+        }
+        stmts
+    }
+
+    /// A block of statements in a new scope
+    fn block(&mut self, indent: usize) -> String {
+        self.scopes.push(Vec::new());
+        let count = 1 + self.rng.below(3);
+        let stmts = self.stmts(indent + 1, count);
+        self.scopes.pop();
+        format!("{{\n{stmts}{}}}", "\t".repeat(indent))
+    }
+
+    fn stmt(&mut self, indent: usize) -> String {
+        match self.rng.below(12) {
+            // declarations
+            0..=2 => {
+                let var_type = self.rng.pick(Type::ALL);
+                let value = match var_type.item() {
+                    Some(item) => self.list(item, 2),
+                    None => self.expr(var_type, 3),
+                };
+                let var = Var { ident: self.ident("v"), var_type, mutable: self.rng.one_in(2) };
+                let stmt = format!("var {}{}: {} = {value}", if var.mutable { "mut " } else { "" }, var.ident, var_type.name());
+                self.scopes.last_mut().unwrap().push(var);
+                stmt
+            },
+
+            // printing
+            3..=4 => match self.rng.below(3) {
+                0 => format!("println!({})", self.expr(Type::Str, 3)),
+                1 => {
+                    let var_type = self.rng.pick(Type::ALL);
+                    format!("println!(as_str!({}))", self.any_expr(var_type, 3))
+                },
+                _ => format!("println!(\"{{}} and {{}}\", {}, {})", self.expr(Type::Num, 2), self.expr(Type::Bool, 2)),
+            },
+
+            // mutations
+            5..=6 => {
+                let Some((ident, var_type)) = self.var(|var| var.mutable && var.var_type.item().is_none()) else { return self.stmt(indent) };
+                match var_type {
+                    Type::Num if self.rng.one_in(2) => {
+                        let op = self.rng.pick(&["+=", "-=", "*="]);
+                        format!("mut {ident} {op} {}", self.expr(Type::Num, 2))
+                    },
+                    Type::Num if self.rng.one_in(2) => format!("mut {ident} /= {}", self.divisor()),
+                    _ => format!("mut {ident} = {}", self.expr(var_type, 3)),
+                }
+            },
+
+            // list operations
+            7 => {
+                let Some((ident, list_type)) = self.var(|var| var.mutable && var.var_type.item().is_some()) else { return self.stmt(indent) };
+                let item = list_type.item().unwrap();
+                match self.rng.below(8) {
+                    0..=2 => format!("list_push!({ident}, {})", self.expr(item, 2)),
+                    3 => format!("list_insert!({ident}, {}, {})", self.index(), self.expr(item, 2)),
+                    4 => format!("list_replace!({ident}, {}, {})", self.index(), self.expr(item, 2)),
+                    5 => format!("list_remove!({ident}, {})", self.index()),
+                    6 => format!("list_sort!({ident})"),
+                    _ => format!("list_reverse!({ident})"),
+                }
+            },
+
+            // control flow
+            8..=9 if indent < 3 => {
+                let cond = self.expr(Type::Bool, 3);
+                let body = self.block(indent);
+                match self.rng.one_in(2) {
+                    true => format!("if ({cond}) {body} else {}", self.block(indent)),
+                    false => format!("if ({cond}) {body}"),
+                }
+            },
+            10 if indent < 3 => {
+                // the counter is immutable within the loop body, so it always halts
+                let counter = self.ident("i");
+                let count = 1 + self.rng.below(4);
+                self.scopes.last_mut().unwrap().push(Var { ident: counter.clone(), var_type: Type::Num, mutable: false });
+                let body = self.block(indent);
+                let tabs = "\t".repeat(indent);
+                format!("var mut {counter} = 0;\n{tabs}while ({counter} < {count}) {{\n{tabs}\t{body};\n{tabs}\tmut {counter} += 1;\n{tabs}}}")
+            },
+
+            // assertions (which rarely fail)
+            11 if self.rng.one_in(2) => format!("assert!({}, \"check\")", self.expr(Type::Bool, 2)),
+            11 => {
+                let var_type = self.rng.pick(&[Type::Num, Type::Str, Type::Bool]);
+                let value = self.expr(var_type, 1);
+                format!("assert_eq!({value}, {value})")
+            },
+
+            _ => format!("println!({})", self.expr(Type::Str, 2)),
+        }
+    }
+
+    /// An expression of any type (including lists)
+    fn any_expr(&mut self, var_type: Type, depth: usize) -> String {
+        match var_type.item() {
+            Some(item) => match self.var(|var| var.var_type == var_type) {
+                Some((ident, _)) if self.rng.one_in(2) => ident,
+                _ => self.list(item, depth),
+            },
+            None => self.expr(var_type, depth),
+        }
+    }
+
+    fn list(&mut self, item: Type, depth: usize) -> String {
+        let items = (0..1 + self.rng.below(4)).map(|_| self.expr(item, depth.saturating_sub(1))).collect::<Vec<_>>();
+        format!("[{}]", items.join(", "))
+    }
+
+    /// A list index that's usually within bounds
+    fn index(&mut self) -> String {
+        self.rng.pick(&["0", "0", "0", "1", "1", "2", "3"]).to_string()
+    }
+
+    fn divisor(&mut self) -> String {
+        self.rng.pick(&["2", "3", "4", "-2", "0.5", "8"]).to_string()
+    }
+
+    fn expr(&mut self, expr_type: Type, depth: usize) -> String {
+        // variables & calls
+        if self.rng.one_in(4) {
+            if let Some((ident, _)) = self.var(|var| var.var_type == expr_type) {
+                return ident;
+            }
+        }
+        if depth > 0 && self.rng.one_in(8) {
+            if let Some(call) = self.call(expr_type, depth) {
+                return call;
+            }
+        }
+        if depth == 0 {
+            return self.literal(expr_type);
+        }
+
+        let depth = depth - 1;
+        match expr_type {
+            Type::Num => match self.rng.below(14) {
+                0..=2 => self.literal(Type::Num),
+                3..=5 => {
+                    let op = self.rng.pick(&["+", "-", "*"]);
+                    format!("({} {op} {})", self.expr(Type::Num, depth), self.expr(Type::Num, depth))
+                },
+                6 => {
+                    let op = self.rng.pick(&["/", "%"]);
+                    format!("({} {op} {})", self.expr(Type::Num, depth), self.divisor())
+                },
+                7 => format!("-({})", self.expr(Type::Num, depth)),
+                8 => format!("str_len!({})", self.expr(Type::Str, depth)),
+                9 => match self.var(|var| var.var_type.item().is_some()) {
+                    Some((ident, _)) => format!("list_len!({ident})"),
+                    None => self.literal(Type::Num),
+                },
+                10 => match self.var(|var| var.var_type == Type::NumList) {
+                    Some((ident, _)) if self.rng.one_in(3) => format!("list_remove!({ident}, {})", self.index()),
+                    Some((ident, _)) => format!("list_get!({ident}, {})", self.index()),
+                    None => self.literal(Type::Num),
+                },
+                11 => match self.var(|var| var.var_type.item().is_some()) {
+                    Some((ident, list_type)) => {
+                        let item = list_type.item().unwrap();
+                        format!("list_index_of!({ident}, {})", self.expr(item, depth))
+                    },
+                    None => self.literal(Type::Num),
+                },
+                12 => format!("char_code!({}, {})", self.expr(Type::Str, depth), self.index()),
+                _ => self.block_expr(Type::Num, depth),
+            },
+            Type::Str => match self.rng.below(12) {
+                0..=2 => self.literal(Type::Str),
+                3..=4 => format!("({} <> {})", self.expr(Type::Str, depth), self.expr(Type::Str, depth)),
+                5 => {
+                    let var_type = self.rng.pick(Type::ALL);
+                    format!("(\"v\" <> as_str!({}))", self.any_expr(var_type, depth))
+                },
+                6 => format!("format!(\"w{{}}x{{}}\", {}, {})", self.expr(Type::Num, depth), self.expr(Type::Bool, depth)),
+                7 => format!("(\"c\" <> str_get!({}, {}))", self.expr(Type::Str, depth), self.index()),
+                8 => format!("from_char_code!({})", 98 + self.rng.below(6)),
+                9 => match self.var(|var| var.var_type == Type::StrList) {
+                    Some((ident, _)) if self.rng.one_in(3) => format!("list_remove!({ident}, {})", self.index()),
+                    Some((ident, _)) => format!("list_get!({ident}, {})", self.index()),
+                    None => self.literal(Type::Str),
+                },
+                _ => self.block_expr(Type::Str, depth),
+            },
+            Type::Bool => match self.rng.below(13) {
+                0..=1 => self.literal(Type::Bool),
+                2..=4 => {
+                    let op = self.rng.pick(&["==", "!=", "<", ">", "<=", ">="]);
+                    format!("({} {op} {})", self.expr(Type::Num, depth), self.expr(Type::Num, depth))
+                },
+                5 => {
+                    let op = self.rng.pick(&["==", "!="]);
+                    format!("({} {op} {})", self.expr(Type::Str, depth), self.expr(Type::Str, depth))
+                },
+                6 => format!("!{}", self.expr(Type::Bool, depth)),
+                7..=8 => {
+                    let op = self.rng.pick(&["&&", "||", "==", "!="]);
+                    format!("({} {op} {})", self.expr(Type::Bool, depth), self.expr(Type::Bool, depth))
+                },
+                9 => match self.var(|var| var.var_type.item().is_some()) {
+                    Some((ident, list_type)) => {
+                        let item = list_type.item().unwrap();
+                        format!("list_contains!({ident}, {})", self.expr(item, depth))
+                    },
+                    None => self.literal(Type::Bool),
+                },
+                10 => {
+                    let list_type = self.rng.pick(&[Type::NumList, Type::StrList]);
+                    let op = self.rng.pick(&["==", "!="]);
+                    format!("({} {op} {})", self.any_expr(list_type, depth), self.any_expr(list_type, depth))
+                },
+                _ => self.block_expr(Type::Bool, depth),
+            },
+            Type::NumList | Type::StrList => unreachable!("lists are only generated by `list`"),
+        }
+    }
+
+    /// A block with a declaration and a tail expression
+    fn block_expr(&mut self, expr_type: Type, depth: usize) -> String {
+        self.scopes.push(Vec::new());
+        let var_type = self.rng.pick(Type::SCALARS);
+        let value = self.expr(var_type, depth);
+        let ident = self.ident("b");
+        self.scopes.last_mut().unwrap().push(Var { ident: ident.clone(), var_type, mutable: false });
+        let tail = self.expr(expr_type, depth);
+        self.scopes.pop();
+        format!("{{ var {ident}: {} = {value}; {tail} }}", var_type.name())
+    }
+
+    fn call(&mut self, return_type: Type, depth: usize) -> Option<String> {
+        let funcs = self.funcs.iter().filter(|func| func.return_type == return_type).collect::<Vec<_>>();
+        if funcs.is_empty() {
+            return None;
+        }
+        let func = funcs[self.rng.below(funcs.len())];
+        let (ident, params) = (func.ident.clone(), func.params.clone());
+
+        let args = params.into_iter()
+            .map(|param| match param.item() {
+                Some(_) => self.any_expr(param, depth - 1),
+                None => self.expr(param, depth - 1),
+            })
+            .collect::<Vec<_>>();
+        Some(format!("{ident}({})", args.join(", ")))
+    }
+
+    fn literal(&mut self, literal_type: Type) -> String {
+        match literal_type {
+            Type::Num => self.rng.pick(&["0", "1", "2", "3", "5", "7", "10", "12", "0.5", "2.25", "100"]).to_string(),
+            Type::Str => {
+                // some strings look like numbers, which scratch compares numerically
+                if self.rng.one_in(6) {
+                    return self.rng.pick(&["\"10\"", "\"9\"", "\"10.0\"", "\"1e1\"", "\" 2\""]).to_string();
+                }
+
+                // letters (in both cases, which scratch ignores) that don't spell out anything scratch would read as a number or boolean
+                let len = self.rng.below(5);
+                let mut string = (0..len).map(|_| self.rng.pick(&['b', 'c', 'd', 'g', 'h', 'k', 'm', 'p', 'B', 'K'])).collect::<String>();
+                if len > 1 && self.rng.one_in(3) {
+                    string.insert(1, ' ');
+                }
+                format!("\"{string}\"")
+            },
+            Type::Bool => self.rng.pick(&["true", "false"]).to_string(),
+            Type::NumList | Type::StrList => unreachable!("lists are only generated by `list`"),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Differential tests between the interpreter (which runs the typed IR) and the scratch emulator (which runs the translated assembly)
+//!
+//! every program is run through both and they must print the same lines and panic at the same place with the same message,
+//! otherwise either the scratch target or the interpreter is miscompiling/misinterpreting something
+
+mod generate;
+
+use std::{fs, path::Path};
+use scrapile::{compile::{self, DEFAULT_CHARSET}, lang::{edition::Edition, interpreter::{self, RuntimeError}, targets}, scratch::emulator};
+
+/// Examples that take too long to emulate in a debug build (run them with `cargo test -- --ignored`)
+const SLOW_EXAMPLES: &[&str] = &["benchmark.srpl"];
+
+/// How many generated programs are checked
+const GENERATED_PROGRAMS: u64 = 256;
+
+/// How a program finished
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    /// Everything written to the console
+    output: String,
+    /// The message of the panic (without the source location) if the program panicked
+    panic: Option<String>,
+}
+
+/// Runs a program through both the interpreter & the emulator and panics if they disagree
+fn check(name: &str, src: &str, input: &str) {
+    let roots = compile::parse(src, Edition::LATEST).unwrap_or_else(|errors| panic!("{name} doesn't parse:\n{errors}"));
+
+    // the interpreter only borrows the project but translating consumes it
+    let project = compile::type_check(&roots).unwrap_or_else(|errors| panic!("{name} doesn't type-check:\n{errors}"));
+    let mut output = Vec::new();
+    let panic = match interpreter::run(&project, name, src, DEFAULT_CHARSET, input.as_bytes(), &mut output) {
+        Ok(()) => None,
+        Err(RuntimeError::Panic { span, message }) => Some((span, message)),
+        Err(err) => panic!("{name} failed to interpret: {err:?}"),
+    };
+    let interpreted = normalise(String::from_utf8(output).unwrap());
+
+    let assembly = targets::scratch::translate(project, name, src, DEFAULT_CHARSET);
+    let mut output = Vec::new();
+    let stage = emulator::run(&assembly, "console", input.as_bytes(), &mut output).unwrap();
+    let mut emulated = normalise(String::from_utf8(output).unwrap());
+
+    // the panic message is the last line pushed to the console before the project stops
+    let emulated_panic = stage.stopped.then(|| {
+        let start = emulated.trim_end_matches('\n').rfind('\n').map_or(0, |idx| idx + 1);
+        let message = emulated.split_off(start);
+        unlocate(message.trim_end_matches('\n'))
+    });
+
+    assert_eq!(
+        Outcome { output: interpreted, panic: panic.map(|(span, message)| format!("<{span:?}> {}", message.lines().next().unwrap_or_default())) },
+        Outcome { output: emulated, panic: emulated_panic },
+        "the interpreter (left) & emulator (right) disagree on {name}:\n{src}",
+    );
+}
+
+/// Moves the source location of a scratch panic message to the front, and strips the prefixes the interpreter doesn't have
+///
+/// (`panic at <1..2>: index out of bounds..` becomes `<1..2> index out of bounds..`)
+fn unlocate(message: &str) -> String {
+    let Some((kind, rest)) = message.split_once(" at <") else { return message.to_string() };
+    let Some((span, rest)) = rest.split_once('>') else { return message.to_string() };
+    let message = match kind {
+        "panic" | "explicit panic" => rest.strip_prefix(": ").unwrap_or(rest).to_string(),
+        _ => format!("{kind}{rest}"),
+    };
+    format!("<{span}> {message}")
+}
+
+/// Hides the parts of a program's output that change between runs (the examples time themselves)
+fn normalise(output: String) -> String {
+    output.lines()
+        .map(|line| match line.strip_prefix("program took ") {
+            Some(_) => "program took <time>s to complete\n".to_string(),
+            None => format!("{line}\n"),
+        })
+        .collect()
+}
+
+/// Checks every `.srpl` file in a directory (except the `skip`ped ones)
+fn check_dir(dir: impl AsRef<Path>, skip: &[&str]) {
+    let mut paths = fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "srpl"))
+        .filter(|path| !skip.iter().any(|name| path.ends_with(name)))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let src = fs::read_to_string(&path).unwrap();
+        let name = path.display().to_string();
+        check(&name, &src, example_input(&path));
+    }
+}
+
+/// What's typed into the examples that ask for input
+fn example_input(path: &Path) -> &'static str {
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some("brainfuck.srpl") => "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.,.\nx\n",
+        Some("showcase.srpl") => "Ada\nLovelace\n",
+        _ => "",
+    }
+}
+
+#[test]
+fn examples() {
+    check_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/examples"), SLOW_EXAMPLES);
+}
+
+#[test]
+#[ignore = "too slow to emulate in a debug build"]
+fn slow_examples() {
+    for name in SLOW_EXAMPLES {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples").join(name);
+        check(name, &fs::read_to_string(&path).unwrap(), example_input(&path));
+    }
+}
+
+#[test]
+fn corpus() {
+    check_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/differential/programs"), &[]);
+}
+
+#[test]
+fn generated() {
+    for seed in 0..GENERATED_PROGRAMS {
+        let src = generate::program(seed);
+        check(&format!("the program generated from seed {seed}"), &src, "");
+    }
+}
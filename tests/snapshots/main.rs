@@ -0,0 +1,157 @@
+//! Golden snapshot tests of the `project.json` generated for the examples & a set of focused programs
+//!
+//! the snapshots are the json (with a line per block) next to the programs (`tests/snapshots/examples/*.json` for the examples),
+//! so changes to the generated projects show up in review; to accept changes, run:
+//!
+//! ```sh
+//! UPDATE_SNAPSHOTS=1 cargo test --test snapshots
+//! git diff tests/snapshots # review them
+//! ```
+
+use std::{env, fs, path::{Path, PathBuf}};
+use json::JsonValue;
+use scrapile::{compile::{self, DEFAULT_CHARSET}, lang::{edition::Edition, targets}, scratch};
+
+/// The most changed lines shown for a snapshot that doesn't match
+const DIFF_LINES: usize = 40;
+
+/// How deep the json is indented before values are kept on one line (so each block & variable gets it's own line)
+const INDENT_DEPTH: usize = 4;
+
+/// Compiles a program into a `project.json` (like `scrapile build` does) for a snapshot
+fn project_json(name: &str, src: &str) -> String {
+    let roots = compile::parse(src, Edition::LATEST).unwrap_or_else(|errors| panic!("{name} doesn't parse:\n{errors}"));
+    let project = compile::type_check(&roots).unwrap_or_else(|errors| panic!("{name} doesn't type-check:\n{errors}"));
+
+    let assembly = targets::scratch::translate(project, name, src, DEFAULT_CHARSET);
+    let json = scratch::add_console("console", scratch::assemble(assembly));
+
+    let mut snapshot = String::new();
+    render(&json, 0, &mut snapshot);
+    snapshot + "\n"
+}
+
+/// Renders json with one value per line up to the `INDENT_DEPTH`
+fn render(json: &JsonValue, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth + 1);
+    let entries = match json {
+        JsonValue::Object(object) if depth < INDENT_DEPTH && !object.is_empty() => object.iter()
+            .map(|(key, value)| (Some(key), value))
+            .collect::<Vec<_>>(),
+        JsonValue::Array(array) if depth < INDENT_DEPTH && !array.is_empty() => array.iter()
+            .map(|value| (None, value))
+            .collect::<Vec<_>>(),
+        json => return out.push_str(&json.dump()),
+    };
+
+    let (open, close) = if json.is_object() { ('{', '}') } else { ('[', ']') };
+    out.push(open);
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        out.push_str(&indent);
+        if let Some(key) = key {
+            out.push_str(&JsonValue::from(key).dump());
+            out.push_str(": ");
+        }
+        render(value, depth + 1, out);
+    }
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+    out.push(close);
+}
+
+/// Checks (or updates) the snapshot of every `.srpl` file in a directory, panicking with all the mismatches at the end
+fn check_dir(dir: impl AsRef<Path>, snapshot_dir: impl AsRef<Path>) {
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some_and(|value| value != "0");
+
+    let mut paths = fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "srpl"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut mismatches = Vec::new();
+    for path in paths {
+        // relative to the crate, so the source locations (like `dbg!`'s labels) are the same on every machine
+        let name = path.strip_prefix(env!("CARGO_MANIFEST_DIR")).unwrap().display().to_string();
+        let actual = project_json(&name, &fs::read_to_string(&path).unwrap());
+        let snapshot = snapshot_path(&path, snapshot_dir.as_ref());
+
+        if update {
+            if fs::read_to_string(&snapshot).ok().as_ref() != Some(&actual) {
+                fs::write(&snapshot, &actual).unwrap();
+                eprintln!("updated {}", snapshot.display());
+            }
+            continue;
+        }
+
+        match fs::read_to_string(&snapshot) {
+            Ok(expected) if expected == actual => (),
+            Ok(expected) => mismatches.push(format!("{} doesn't match:\n{}", snapshot.display(), diff(&expected, &actual))),
+            Err(_) => mismatches.push(format!("{} is missing", snapshot.display())),
+        }
+    }
+
+    if !mismatches.is_empty() {
+        panic!("{}\n\nif these changes are expected, update the snapshots with `UPDATE_SNAPSHOTS=1 cargo test --test snapshots`", mismatches.join("\n\n"));
+    }
+}
+
+/// Where the snapshot of a program is stored
+fn snapshot_path(path: &Path, snapshot_dir: &Path) -> PathBuf {
+    snapshot_dir.join(path.file_name().unwrap()).with_extension("json")
+}
+
+/// The lines that changed between two snapshots (from the longest common subsequence of their lines)
+fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+
+    // the length of the longest common subsequence of the lines after each pair of lines
+    let mut lengths = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i][j] = if expected[i] == actual[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changed = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            (i, j) = (i + 1, j + 1);
+        } else if i < expected.len() && (j == actual.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            changed.push(format!("{:>5} -{}", i + 1, expected[i]));
+            i += 1;
+        } else {
+            changed.push(format!("{:>5} +{}", j + 1, actual[j]));
+            j += 1;
+        }
+    }
+
+    let mut diff = changed.iter().take(DIFF_LINES).cloned().collect::<Vec<_>>().join("\n");
+    if changed.len() > DIFF_LINES {
+        diff.push_str(&format!("\n... and {} more changed lines", changed.len() - DIFF_LINES));
+    }
+    diff
+}
+
+#[test]
+fn examples() {
+    check_dir(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/examples"),
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/examples"),
+    );
+}
+
+#[test]
+fn programs() {
+    check_dir(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/programs"),
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/programs"),
+    );
+}
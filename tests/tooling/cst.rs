@@ -0,0 +1,31 @@
+use scrapile::lang::{cst, edition::Edition};
+use crate::sources;
+
+#[test]
+fn lex_is_lossless() {
+    for (path, src) in sources() {
+        let tokens = cst::lex(&src, Edition::LATEST);
+
+        // the tokens must cover the source without any gaps or overlaps
+        let mut pos = 0;
+        for token in tokens.iter() {
+            assert_eq!(token.span.start, pos, "{} has a gap or overlap before {token:?}", path.display());
+            pos = token.span.end;
+        }
+        assert_eq!(pos, src.len(), "{} isn't lexed to the end", path.display());
+
+        let text = tokens.iter().map(|token| &src[token.span.clone()]).collect::<String>();
+        assert_eq!(text, src, "the tokens of {} don't reproduce it", path.display());
+    }
+}
+
+#[test]
+fn parse_is_lossless() {
+    for (path, src) in sources() {
+        let root = cst::parse(&src, Edition::LATEST);
+        assert_eq!(root.text(&src), src, "the root of {} doesn't span all of it", path.display());
+
+        let text = root.tokens().into_iter().map(|token| &src[token.span.clone()]).collect::<String>();
+        assert_eq!(text, src, "the tree of {} doesn't reproduce it", path.display());
+    }
+}
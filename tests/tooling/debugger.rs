@@ -0,0 +1,90 @@
+use scrapile::{compile::{self, DEFAULT_CHARSET}, lang::{debugger::Debugger, edition::Edition, interpreter::{self, RuntimeError}}};
+
+const SRC: &str = "\
+fn double(n: num) -> num {
+    n * 2
+}
+
+main {
+    var mut i = 0;
+    while (i < 3) { mut i += 1; };
+    var x = double(i);
+
+    println!(as_str!(x));
+}
+";
+
+/// Runs `SRC` in the debugger with the commands, returning the result, the program's output & the debugger's console
+fn debug(breakpoints: &[usize], commands: &str) -> (Result<(), RuntimeError>, String, String) {
+    let roots = compile::parse(SRC, Edition::LATEST).unwrap();
+    let project = compile::type_check(&roots).unwrap();
+
+    let (mut output, mut console) = (Vec::new(), Vec::new());
+    let mut debugger = Debugger::new(SRC, "main.srpl", Edition::LATEST, breakpoints.iter().copied(), &mut console);
+    let result = interpreter::run_with_hook(&project, "main.srpl", SRC, DEFAULT_CHARSET, commands.as_bytes(), &mut output, &mut debugger);
+    drop(debugger);
+
+    (result, String::from_utf8(output).unwrap(), String::from_utf8(console).unwrap())
+}
+
+/// Asserts that the lines appear in the console in order
+#[track_caller]
+fn assert_in_order(console: &str, lines: &[&str]) {
+    let mut rest = console;
+    for line in lines {
+        match rest.find(line) {
+            Some(idx) => rest = &rest[idx + line.len()..],
+            None => panic!("expected `{line}` next in the console:\n{console}"),
+        }
+    }
+}
+
+#[test]
+fn scripted_session() {
+    let commands = "b 9\nb 7\nc\np i\nc\n\np i\nd 7\nb 2\nc\nbt\nl\no\np x\np n\nc\n";
+    let (result, output, console) = debug(&[], commands);
+
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(output, "6\n");
+    assert_in_order(&console, &[
+        // without breakpoints it pauses before the first statement
+        "main.srpl:6 (in main)",
+        "(debug) breakpoint set on line 9 (but no statements start on it, so it won't be hit)",
+        "(debug) breakpoint set on line 7",
+        // the breakpoint on a one-line loop is hit for every iteration
+        "main.srpl:7 (in main)", "i = 0",
+        // (and an empty line repeats the previous command)
+        "main.srpl:7 (in main)",
+        "main.srpl:7 (in main)", "i = 2",
+        "(debug) breakpoint removed from line 7",
+        "(debug) breakpoint set on line 2",
+        "main.srpl:2 (in double)",
+        "#0 double at main.srpl:2\n#1 main at main.srpl:8",
+        "(debug) n = 3",
+        // stepping out of `double` pauses after it returns
+        "main.srpl:10 (in main)",
+        "(debug) x = 6",
+        "(debug) no variable named `n` is visible here",
+    ]);
+}
+
+#[test]
+fn breakpoints_are_hit_when_their_line_is_entered() {
+    // every statement on line 7 only hits it once per iteration
+    let (result, output, console) = debug(&[7], "c\nc\nc\n");
+    assert!(result.is_ok(), "{result:?}");
+    assert_eq!(output, "6\n");
+    assert_eq!(console.matches("main.srpl:7 (in main)").count(), 3, "{console}");
+}
+
+#[test]
+fn quitting_stops_the_program() {
+    let (result, output, _) = debug(&[8], "q\n");
+    assert!(matches!(result, Err(RuntimeError::Quit)), "{result:?}");
+    assert_eq!(output, "");
+
+    // and so does running out of commands
+    let (result, output, _) = debug(&[], "n\n");
+    assert!(matches!(result, Err(RuntimeError::Quit)), "{result:?}");
+    assert_eq!(output, "");
+}
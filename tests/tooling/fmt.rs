@@ -0,0 +1,32 @@
+use scrapile::lang::{cst::{self, CstTokenKind, TriviaKind}, edition::Edition, fmt};
+use crate::sources;
+
+/// The text of every token that isn't whitespace (so comments are kept), without trailing commas (which the formatter adds & removes)
+fn significant_tokens(src: &str) -> Vec<&str> {
+    let tokens = cst::lex(src, Edition::LATEST).into_iter()
+        .filter(|token| token.kind != CstTokenKind::Trivia(TriviaKind::Whitespace))
+        .map(|token| &src[token.span])
+        .collect::<Vec<_>>();
+
+    tokens.iter()
+        .enumerate()
+        .filter(|(i, token)| !(**token == "," && matches!(tokens.get(i + 1), Some(&")" | &"]"))))
+        .map(|(_, token)| *token)
+        .collect()
+}
+
+#[test]
+fn format_is_idempotent() {
+    for (path, src) in sources() {
+        let formatted = fmt::format(&src, Edition::LATEST);
+        assert_eq!(fmt::format(&formatted, Edition::LATEST), formatted, "formatting {} twice changes it", path.display());
+    }
+}
+
+#[test]
+fn format_only_changes_whitespace() {
+    for (path, src) in sources() {
+        let formatted = fmt::format(&src, Edition::LATEST);
+        assert_eq!(significant_tokens(&formatted), significant_tokens(&src), "formatting {} changes more than whitespace", path.display());
+    }
+}
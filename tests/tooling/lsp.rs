@@ -0,0 +1,46 @@
+use json::object;
+use scrapile::lsp::{offset_to_position, position_to_offset, uri_to_path};
+
+/// A line with characters that take up 2 (`é`), 3 (`☺`) & 4 (`𝄞`, two utf-16 code units) bytes
+const SRC: &str = "main {\n    println!(\"é☺𝄞x\");\n}\n";
+
+#[test]
+fn positions_count_utf16_code_units() {
+    let x = SRC.find('x').unwrap();
+    // `    println!("` is 14 code units, then `é`, `☺` & the two of `𝄞`
+    assert_eq!(offset_to_position(SRC, x), object! { line: 1, character: 18 });
+    assert_eq!(position_to_offset(SRC, &object! { line: 1, character: 18 }), x);
+
+    let clef = SRC.find('𝄞').unwrap();
+    assert_eq!(offset_to_position(SRC, clef), object! { line: 1, character: 16 });
+    assert_eq!(position_to_offset(SRC, &object! { line: 1, character: 16 }), clef);
+}
+
+#[test]
+fn positions_round_trip() {
+    for (offset, _) in SRC.char_indices() {
+        assert_eq!(position_to_offset(SRC, &offset_to_position(SRC, offset)), offset, "offset {offset} doesn't round-trip");
+    }
+}
+
+#[test]
+fn positions_past_the_end_are_clamped() {
+    // past the end of a line is the end of that line
+    assert_eq!(position_to_offset(SRC, &object! { line: 0, character: 100 }), SRC.find('\n').unwrap());
+    // past the last line is the end of the source
+    assert_eq!(position_to_offset(SRC, &object! { line: 10, character: 0 }), SRC.len());
+    assert_eq!(offset_to_position(SRC, SRC.len() + 10), object! { line: 3, character: 0 });
+}
+
+#[test]
+fn uris_are_percent_decoded() {
+    assert_eq!(uri_to_path("file:///home/ada/main.srpl").as_deref(), Some("/home/ada/main.srpl"));
+    assert_eq!(uri_to_path("file:///home/ada/my%20project/caf%C3%A9.srpl").as_deref(), Some("/home/ada/my project/café.srpl"));
+    assert_eq!(uri_to_path("file:///C%3A/main.srpl").as_deref(), Some("/C:/main.srpl"));
+
+    // only valid file uris are paths
+    assert_eq!(uri_to_path("https://example.com/main.srpl"), None);
+    assert_eq!(uri_to_path("file:///bad%2"), None);
+    assert_eq!(uri_to_path("file:///bad%zz"), None);
+    assert_eq!(uri_to_path("file:///not-utf8%FF"), None);
+}
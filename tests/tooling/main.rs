@@ -0,0 +1,27 @@
+//! Tests of the developer tooling (the concrete syntax tree, formatter, language server, repl & debugger)
+
+mod cst;
+mod debugger;
+mod fmt;
+mod lsp;
+mod repl;
+
+use std::{fs, path::PathBuf};
+
+/// The paths & source code of the examples & test programs (so the tooling is checked against every feature of the language)
+pub fn sources() -> Vec<(PathBuf, String)> {
+    let dirs = ["examples", "tests/differential/programs", "tests/snapshots/programs"];
+    let mut paths = dirs.iter()
+        .flat_map(|dir| fs::read_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(dir)).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "srpl"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths.into_iter()
+        .map(|path| {
+            let src = fs::read_to_string(&path).unwrap();
+            (path, src)
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+use std::io;
+use scrapile::{compile::DEFAULT_CHARSET, lang::{edition::Edition, interpreter::{RuntimeError, Value}, repl::{Repl, ReplError}, typed::types::Type}};
+
+/// Evaluates an entry without any input, returning it's result & output
+fn eval(repl: &mut Repl, entry: &str) -> (Result<Option<(Value, Type)>, ReplError>, String) {
+    let mut output = Vec::new();
+    let result = repl.eval(entry, io::empty(), &mut output);
+    (result, String::from_utf8(output).unwrap())
+}
+
+/// Evaluates an entry that must succeed, returning it's value (if it has one)
+fn value(repl: &mut Repl, entry: &str) -> Option<(Value, Type)> {
+    eval(repl, entry).0.unwrap_or_else(|err| panic!("`{entry}` failed: {err:?}"))
+}
+
+#[test]
+fn variables_persist_between_entries() {
+    let mut repl = Repl::new(Edition::LATEST, DEFAULT_CHARSET.to_vec());
+
+    assert_eq!(value(&mut repl, "var mut x = 2;"), None);
+    assert_eq!(value(&mut repl, "x * 3"), Some((Value::Number(6.0), Type::Number)));
+    assert_eq!(value(&mut repl, "mut x += 1; x"), Some((Value::Number(3.0), Type::Number)));
+    assert_eq!(value(&mut repl, "var name = \"ada\"; name <> \"!\""), Some((Value::String("ada!".to_string()), Type::String)));
+
+    // statements without a value (or with a `nil` one) print nothing
+    let (result, output) = eval(&mut repl, "println!(name)");
+    assert!(matches!(result, Ok(None)));
+    assert_eq!(output, "ada\n");
+}
+
+#[test]
+fn functions_can_be_defined_and_called() {
+    let mut repl = Repl::new(Edition::LATEST, DEFAULT_CHARSET.to_vec());
+
+    // functions in the same entry can call each other
+    assert_eq!(value(&mut repl, "fn double(n: num) -> num { n * 2 } fn quadruple(n: num) -> num { double(double(n)) }"), None);
+    assert_eq!(value(&mut repl, "quadruple(3)"), Some((Value::Number(12.0), Type::Number)));
+
+    // as can functions from later entries
+    assert_eq!(value(&mut repl, "fn octuple(n: num) -> num { double(quadruple(n)) }"), None);
+    assert_eq!(value(&mut repl, "octuple(1)"), Some((Value::Number(8.0), Type::Number)));
+
+    // but they can't be re-defined, and neither can main procedures or tests
+    assert!(matches!(eval(&mut repl, "fn double(n: num) -> num { n }").0, Err(ReplError::Compile(_))));
+    assert!(matches!(eval(&mut repl, "main {}").0, Err(ReplError::Compile(_))));
+    assert!(matches!(eval(&mut repl, "test fn works() -> nil {}").0, Err(ReplError::Compile(_))));
+    assert_eq!(value(&mut repl, "double(1)"), Some((Value::Number(2.0), Type::Number)));
+}
+
+#[test]
+fn failed_entries_are_rolled_back() {
+    let mut repl = Repl::new(Edition::LATEST, DEFAULT_CHARSET.to_vec());
+    assert_eq!(value(&mut repl, "var mut x = 1;"), None);
+
+    // a panic undoes the entry's mutations & declarations (but not it's output)
+    let (result, output) = eval(&mut repl, "mut x = 10; var y = 2; println!(\"before\"); panic!(\"boom\");");
+    assert!(matches!(result, Err(ReplError::Runtime(RuntimeError::Panic { message, .. })) if message == "boom"));
+    assert_eq!(output, "before\n");
+    assert_eq!(value(&mut repl, "x"), Some((Value::Number(1.0), Type::Number)));
+    assert!(matches!(eval(&mut repl, "y").0, Err(ReplError::Compile(_))));
+
+    // an entry that doesn't type-check isn't run at all
+    let (result, output) = eval(&mut repl, "mut x = 5; println!(\"never\"); x <> \"a\"");
+    assert!(matches!(result, Err(ReplError::Compile(_))));
+    assert_eq!(output, "");
+    assert_eq!(value(&mut repl, "x"), Some((Value::Number(1.0), Type::Number)));
+
+    // and the repl keeps working afterwards
+    assert_eq!(value(&mut repl, "mut x = 3; x"), Some((Value::Number(3.0), Type::Number)));
+}
+
+#[test]
+fn unfinished_entries_continue() {
+    let repl = Repl::new(Edition::LATEST, DEFAULT_CHARSET.to_vec());
+    assert!(repl.is_unfinished("fn double(n: num) -> num {"));
+    assert!(repl.is_unfinished("println!(as_str!([1,"));
+    assert!(!repl.is_unfinished("fn double(n: num) -> num { n * 2 }"));
+    assert!(!repl.is_unfinished("1 + 2"));
+}